@@ -1,2 +1,2 @@
 #!/bin/sh
-exec cargo test day11
+exec cargo run --release --bin aoc -- --day 11
@@ -0,0 +1,124 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Context};
+
+use crate::Part;
+
+/// What we expect a day's part to produce on our own input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Expected {
+    Known(String),
+    /// Not solved yet, or not recorded; the answer is reported but not checked.
+    Unknown,
+    /// Deliberately not run, e.g. day 25 has no second part.
+    Skipped,
+}
+
+/// Recorded answers for the real puzzle inputs, stored as
+/// `<day> <part> known <answer>`, `<day> <part> unknown` or
+/// `<day> <part> skip` lines. `#` starts a comment; anything not listed
+/// is unknown.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Answers(BTreeMap<(u8, Part), Expected>);
+
+impl Answers {
+    /// `answers.txt` in this checkout, unless `AOC_ANSWERS` names another file.
+    pub fn default_path() -> PathBuf {
+        match std::env::var("AOC_ANSWERS") {
+            Ok(p) => PathBuf::from(p),
+            Err(_) => Path::new(env!("CARGO_MANIFEST_DIR")).join("answers.txt"),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Answers, anyhow::Error> {
+        let text =
+            std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        text.parse()
+            .with_context(|| format!("in {}", path.display()))
+    }
+
+    pub fn get(&self, day: u8, part: Part) -> &Expected {
+        self.0.get(&(day, part)).unwrap_or(&Expected::Unknown)
+    }
+}
+
+impl std::str::FromStr for Answers {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut out = BTreeMap::new();
+        for (n, line) in s.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let err = || {
+                anyhow!(
+                    "line {}: expected '<day> <part> known|unknown|skip [answer]'",
+                    n + 1
+                )
+            };
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let (day, part) = match fields[..] {
+                [d, p, ..] => (d.parse::<u8>()?, p.parse::<Part>()?),
+                _ => return Err(err()),
+            };
+            let expected = match fields[2..] {
+                ["known", answer] => Expected::Known(answer.to_string()),
+                ["unknown"] => Expected::Unknown,
+                ["skip"] => Expected::Skipped,
+                _ => return Err(err()),
+            };
+
+            if out.insert((day, part), expected).is_some() {
+                return Err(anyhow!(
+                    "line {}: day {day} part {part} listed twice",
+                    n + 1
+                ));
+            }
+        }
+
+        Ok(Answers(out))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let a: Answers = "# day part state answer
+            1 1 known 11
+            1 2 known 31  # sample answers
+            17 1 known 4,6,3,5,6,3,5,2,1,0
+            24 2 unknown
+            25 2 skip
+        "
+        .parse()
+        .unwrap();
+
+        assert_eq!(&Expected::Known("31".to_string()), a.get(1, Part::Two));
+        assert_eq!(
+            &Expected::Known("4,6,3,5,6,3,5,2,1,0".to_string()),
+            a.get(17, Part::One)
+        );
+        assert_eq!(&Expected::Unknown, a.get(24, Part::Two));
+        assert_eq!(&Expected::Unknown, a.get(3, Part::One));
+        assert_eq!(&Expected::Skipped, a.get(25, Part::Two));
+
+        assert!("1 1 known".parse::<Answers>().is_err());
+        assert!("1 3 known 5".parse::<Answers>().is_err());
+        assert!("1 1 known 5\n1 1 known 6".parse::<Answers>().is_err());
+    }
+
+    #[test]
+    fn checked_in() {
+        let a = Answers::load(&Path::new(env!("CARGO_MANIFEST_DIR")).join("answers.txt")).unwrap();
+        assert_eq!(&Expected::Skipped, a.get(25, Part::Two));
+    }
+}
@@ -0,0 +1,335 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    fmt::Display,
+    panic::{catch_unwind, AssertUnwindSafe},
+    str::FromStr,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+use anyhow::anyhow;
+use regex::Regex;
+
+use crate::{Part, Solution};
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// Wraps the system allocator to count allocations. Install it in a binary
+/// with `#[global_allocator]`; without it every allocation count reads 0.
+pub struct CountingAlloc;
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Stage {
+    Parse,
+    Part1,
+    Part2,
+}
+
+impl Display for Stage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Stage::Parse => "parse",
+            Stage::Part1 => "part1",
+            Stage::Part2 => "part2",
+        })
+    }
+}
+
+impl FromStr for Stage {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "parse" => Ok(Stage::Parse),
+            "part1" => Ok(Stage::Part1),
+            "part2" => Ok(Stage::Part2),
+            _ => Err(anyhow!("unknown stage '{s}'")),
+        }
+    }
+}
+
+/// Timings for one stage of one day over a number of runs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Stats {
+    pub day: u8,
+    pub stage: Stage,
+    pub runs: usize,
+    pub min: Duration,
+    pub median: Duration,
+    pub p95: Duration,
+    /// Fewest allocations seen in a single run.
+    pub allocs: usize,
+}
+
+impl Stats {
+    fn from_samples(day: u8, stage: Stage, mut samples: Vec<(Duration, usize)>) -> Stats {
+        assert!(!samples.is_empty());
+        let allocs = samples.iter().map(|s| s.1).min().unwrap_or_default();
+        samples.sort();
+
+        let n = samples.len();
+        // nearest-rank percentiles
+        let rank = |p: usize| samples[(n * p).div_ceil(100).max(1) - 1].0;
+
+        Stats {
+            day,
+            stage,
+            runs: n,
+            min: samples[0].0,
+            median: rank(50),
+            p95: rank(95),
+            allocs,
+        }
+    }
+}
+
+fn time<F: FnMut() -> bool>(runs: usize, mut f: F) -> Option<Vec<(Duration, usize)>> {
+    let mut samples = Vec::with_capacity(runs);
+    for _ in 0..runs {
+        let allocs = ALLOCATIONS.load(Ordering::Relaxed);
+        let start = Instant::now();
+        if !f() {
+            return None;
+        }
+        let elapsed = start.elapsed();
+        samples.push((elapsed, ALLOCATIONS.load(Ordering::Relaxed) - allocs));
+    }
+
+    Some(samples)
+}
+
+/// Run each stage of `s` against `input` `runs` times. Stages the day
+/// doesn't support (no standalone parser, an unwritten part that panics,
+/// or an input it rejects) are left out.
+pub fn bench(s: &dyn Solution, input: &str, runs: usize) -> Vec<Stats> {
+    let mut out = vec![];
+
+    if s.validate(input).is_some() {
+        if let Some(samples) = time(runs, || matches!(s.validate(input), Some(Ok(())))) {
+            out.push(Stats::from_samples(s.day(), Stage::Parse, samples));
+        }
+    }
+
+    for (stage, part) in [(Stage::Part1, Part::One), (Stage::Part2, Part::Two)] {
+        let samples = time(runs, || {
+            catch_unwind(AssertUnwindSafe(|| s.try_solve(part, input).is_ok())).unwrap_or(false)
+        });
+        if let Some(samples) = samples {
+            out.push(Stats::from_samples(s.day(), stage, samples));
+        }
+    }
+
+    out
+}
+
+/// Machine-readable form of a set of results, one object per line.
+pub fn to_json(stats: &[Stats]) -> String {
+    let rows: Vec<String> = stats
+        .iter()
+        .map(|s| {
+            format!(
+                r#"  {{"day": {}, "stage": "{}", "runs": {}, "min_ns": {}, "median_ns": {}, "p95_ns": {}, "allocs": {}}}"#,
+                s.day,
+                s.stage,
+                s.runs,
+                s.min.as_nanos(),
+                s.median.as_nanos(),
+                s.p95.as_nanos(),
+                s.allocs
+            )
+        })
+        .collect();
+
+    format!("[\n{}\n]\n", rows.join(",\n"))
+}
+
+/// Read back results written by `to_json`. This isn't a general JSON
+/// parser: it takes each `{...}` as one result and picks its fields out by
+/// name, so the objects mustn't nest, but layout and key order are free.
+pub fn from_json(json: &str) -> Result<Vec<Stats>, anyhow::Error> {
+    let field = Regex::new(r#""(\w+)":\s*"?([\w.]+)"?"#).expect("regex error");
+
+    let mut objects = vec![];
+    let mut rest = json;
+    while let Some(start) = rest.find('{') {
+        let len = rest[start..]
+            .find('}')
+            .ok_or_else(|| anyhow!("unclosed object: {}", rest[start..].trim()))?;
+        objects.push(&rest[start..=start + len]);
+        rest = &rest[start + len + 1..];
+    }
+
+    objects
+        .into_iter()
+        .map(|object| {
+            let get = |name: &str| {
+                field
+                    .captures_iter(object)
+                    .find(|c| &c[1] == name)
+                    .map(|c| c[2].to_string())
+                    .ok_or_else(|| anyhow!("missing \"{name}\" in {object}"))
+            };
+            let ns = |name: &str| -> Result<Duration, anyhow::Error> {
+                Ok(Duration::from_nanos(get(name)?.parse()?))
+            };
+
+            Ok(Stats {
+                day: get("day")?.parse()?,
+                stage: get("stage")?.parse()?,
+                runs: get("runs")?.parse()?,
+                min: ns("min_ns")?,
+                median: ns("median_ns")?,
+                p95: ns("p95_ns")?,
+                allocs: get("allocs")?.parse()?,
+            })
+        })
+        .collect()
+}
+
+/// A stage whose median got slower than its baseline by more than the
+/// allowed tolerance.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Regression {
+    pub day: u8,
+    pub stage: Stage,
+    pub baseline: Duration,
+    pub current: Duration,
+}
+
+impl Display for Regression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let pct = (self.current.as_secs_f64() / self.baseline.as_secs_f64() - 1.0) * 100.0;
+        write!(
+            f,
+            "day {} {}: {:.2?} -> {:.2?} (+{pct:.0}%)",
+            self.day, self.stage, self.baseline, self.current
+        )
+    }
+}
+
+/// Compare medians against a baseline; `tolerance` is a fraction, so 0.1
+/// lets a stage get 10% slower before it's flagged.
+pub fn compare(baseline: &[Stats], current: &[Stats], tolerance: f64) -> Vec<Regression> {
+    current
+        .iter()
+        .filter_map(|c| {
+            let b = baseline
+                .iter()
+                .find(|b| b.day == c.day && b.stage == c.stage)?;
+            let limit = b.median.as_secs_f64() * (1.0 + tolerance);
+
+            (c.median.as_secs_f64() > limit).then_some(Regression {
+                day: c.day,
+                stage: c.stage,
+                baseline: b.median,
+                current: c.median,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::solution;
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    #[test]
+    fn percentiles() {
+        let samples = (1..=20).rev().map(|n| (ms(n), 3)).collect();
+        let s = Stats::from_samples(1, Stage::Part1, samples);
+
+        assert_eq!(20, s.runs);
+        assert_eq!(ms(1), s.min);
+        assert_eq!(ms(10), s.median);
+        assert_eq!(ms(19), s.p95);
+        assert_eq!(3, s.allocs);
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let stats = vec![
+            Stats::from_samples(16, Stage::Part2, vec![(ms(300), 10), (ms(320), 12)]),
+            Stats::from_samples(1, Stage::Parse, vec![(Duration::from_nanos(1234), 0)]),
+        ];
+
+        assert_eq!(stats, from_json(&to_json(&stats)).unwrap());
+
+        // pretty-printed, with the keys in another order
+        let reformatted = r#"[
+            {
+                "stage": "part2",
+                "day": 16,
+                "runs": 2,
+                "allocs": 10,
+                "p95_ns": 320000000,
+                "median_ns": 300000000,
+                "min_ns": 300000000
+            },
+            {
+                "min_ns": 1234, "median_ns": 1234, "p95_ns": 1234,
+                "day": 1, "stage": "parse", "runs": 1, "allocs": 0
+            }
+        ]"#;
+        assert_eq!(stats, from_json(reformatted).unwrap());
+
+        assert!(from_json("[{\"day\": 1").is_err());
+    }
+
+    #[test]
+    fn regressions() {
+        let base = vec![
+            Stats::from_samples(16, Stage::Part2, vec![(ms(300), 0)]),
+            Stats::from_samples(22, Stage::Part2, vec![(ms(300), 0)]),
+        ];
+        let now = vec![
+            Stats::from_samples(16, Stage::Part2, vec![(ms(320), 0)]),
+            Stats::from_samples(22, Stage::Part2, vec![(ms(400), 0)]),
+            Stats::from_samples(23, Stage::Part1, vec![(ms(400), 0)]),
+        ];
+
+        let r = compare(&base, &now, 0.1);
+        assert_eq!(1, r.len());
+        assert_eq!(22, r[0].day);
+        assert_eq!(
+            "day 22 part2: 300.00ms -> 400.00ms (+33%)",
+            r[0].to_string()
+        );
+    }
+
+    #[test]
+    fn stages() {
+        let input = "3   4\n4   3\n2   5\n1   3\n3   9\n3   3\n";
+        let stats = bench(solution(1).unwrap(), input, 5);
+        let stages: Vec<_> = stats.iter().map(|s| s.stage).collect();
+        assert_eq!(vec![Stage::Parse, Stage::Part1, Stage::Part2], stages);
+
+        // day 9 has no standalone parser
+        let stats = bench(solution(9).unwrap(), "12345", 2);
+        assert!(stats.iter().all(|s| s.stage != Stage::Parse));
+    }
+}
@@ -0,0 +1,272 @@
+use std::{collections::BTreeMap, env, process::ExitCode, time::Instant};
+
+use aoc24_rs::{
+    bench, compare, fetch_input, from_json, solution, to_json, CountingAlloc, HttpProvider, Ledger,
+    Part, Stats,
+};
+
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
+
+const USAGE: &str = "usage: aoc --day <1-25> [--part 1|2|both] [--param KEY=VALUE]...
+           [--submit] [--ledger FILE]
+       aoc --day <1-25> --render FILE|- [--scale N] [--param KEY=VALUE]...
+       aoc --bench [--day <1-25>] [--runs N] [--json FILE] [--baseline FILE]";
+
+/// How much slower than the baseline a median can get before we complain.
+const TOLERANCE: f64 = 0.1;
+
+struct Args {
+    day: Option<u8>,
+    parts: Vec<Part>,
+    params: BTreeMap<String, String>,
+    bench: bool,
+    runs: usize,
+    json: Option<String>,
+    baseline: Option<String>,
+    submit: bool,
+    ledger: String,
+    render: Option<String>,
+    scale: usize,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut a = Args {
+        day: None,
+        parts: vec![Part::One, Part::Two],
+        params: BTreeMap::new(),
+        bench: false,
+        runs: 10,
+        json: None,
+        baseline: None,
+        submit: false,
+        ledger: "submissions.txt".to_string(),
+        render: None,
+        scale: 4,
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{arg} needs a value"));
+        match arg.as_str() {
+            "-d" | "--day" => {
+                let v = value()?;
+                let d: u8 = v.parse().map_err(|_| format!("bad day '{v}'"))?;
+                if !(1..=25).contains(&d) {
+                    return Err(format!("day must be 1-25, got {d}"));
+                }
+                a.day = Some(d);
+            }
+            "-p" | "--part" => {
+                let v = value()?;
+                a.parts = match v.as_str() {
+                    "1" => vec![Part::One],
+                    "2" => vec![Part::Two],
+                    "both" => vec![Part::One, Part::Two],
+                    _ => return Err(format!("part must be 1, 2 or both, got '{v}'")),
+                };
+            }
+            "--param" => {
+                let v = value()?;
+                let (k, val) = v
+                    .split_once('=')
+                    .ok_or(format!("--param needs KEY=VALUE, got '{v}'"))?;
+                a.params
+                    .insert(k.trim().to_string(), val.trim().to_string());
+            }
+            "--bench" => a.bench = true,
+            "--runs" => {
+                let v = value()?;
+                a.runs = v.parse().map_err(|_| format!("bad run count '{v}'"))?;
+                if a.runs == 0 {
+                    return Err("--runs must be at least 1".to_string());
+                }
+            }
+            "--json" => a.json = Some(value()?),
+            "--baseline" => a.baseline = Some(value()?),
+            "--submit" => a.submit = true,
+            "--ledger" => a.ledger = value()?,
+            "--render" => a.render = Some(value()?),
+            "--scale" => {
+                let v = value()?;
+                a.scale = v.parse().map_err(|_| format!("bad scale '{v}'"))?;
+            }
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            _ => return Err(format!("unexpected argument '{arg}'")),
+        }
+    }
+
+    if a.day.is_none() && !a.bench {
+        return Err("--day is required".to_string());
+    }
+
+    Ok(a)
+}
+
+fn solve(
+    day: u8,
+    parts: &[Part],
+    params: &BTreeMap<String, String>,
+    ledger: Option<&mut Ledger>,
+) -> ExitCode {
+    let solution = solution(day).expect("days 1-25 are all registered");
+    let input = match fetch_input(day) {
+        Ok(i) => i,
+        Err(e) => {
+            eprintln!("couldn't fetch input for day {day}: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut answers = vec![];
+    for &part in parts {
+        let start = Instant::now();
+        let answer = solution.try_solve_with(part, &input, params);
+        let elapsed = start.elapsed();
+        match answer {
+            Ok(answer) => {
+                println!("day {day} part {part}: {answer} ({elapsed:.2?})");
+                answers.push((part, answer.to_string()));
+            }
+            Err(e) => {
+                eprintln!("day {day} part {part} failed: {e}");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    let Some(ledger) = ledger else {
+        return ExitCode::SUCCESS;
+    };
+    for (part, answer) in answers {
+        if let Err(e) = submit(ledger, day, part, &answer) {
+            eprintln!("couldn't submit day {day} part {part}: {e}");
+            return ExitCode::FAILURE;
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+fn submit(ledger: &mut Ledger, day: u8, part: Part, answer: &str) -> Result<(), anyhow::Error> {
+    if let Some(why) = ledger.objection(day, part, answer) {
+        println!("not submitting day {day} part {part}: {why}");
+        return Ok(());
+    }
+
+    let verdict = HttpProvider::from_env()?.submit(day, part, answer)?;
+    println!("day {day} part {part}: {verdict}");
+    ledger.record(day, part, answer, verdict)
+}
+
+/// Draw the day's picture: ANSI to the terminal for `-`, otherwise a PNG
+/// or text file depending on `out`'s extension.
+fn render(
+    day: u8,
+    params: &BTreeMap<String, String>,
+    out: &str,
+    scale: usize,
+) -> Result<(), anyhow::Error> {
+    let input = fetch_input(day)?;
+    let picture = solution(day)
+        .expect("days 1-25 are all registered")
+        .render(&input, params)
+        .ok_or_else(|| anyhow::anyhow!("day {day} doesn't draw anything"))??;
+
+    if out == "-" {
+        print!("{}", picture.ansi());
+    } else {
+        picture.save(out, scale)?;
+        println!("wrote day {day} to {out}");
+    }
+    Ok(())
+}
+
+fn run_bench(args: &Args) -> Result<bool, anyhow::Error> {
+    let days = match args.day {
+        Some(d) => vec![d],
+        None => (1..=25).collect(),
+    };
+
+    println!(
+        "{:>3} {:<6} {:>10} {:>10} {:>10} {:>8}",
+        "day", "stage", "min", "median", "p95", "allocs"
+    );
+
+    let mut all: Vec<Stats> = vec![];
+    for day in days {
+        let input = match fetch_input(day) {
+            Ok(i) => i,
+            Err(e) => {
+                eprintln!("skipping day {day}: {e}");
+                continue;
+            }
+        };
+
+        for s in bench(solution(day).expect("registered"), &input, args.runs) {
+            println!(
+                "{:>3} {:<6} {:>10.2?} {:>10.2?} {:>10.2?} {:>8}",
+                s.day, s.stage, s.min, s.median, s.p95, s.allocs
+            );
+            all.push(s);
+        }
+    }
+
+    if let Some(path) = &args.json {
+        std::fs::write(path, to_json(&all))?;
+    }
+
+    let Some(path) = &args.baseline else {
+        return Ok(true);
+    };
+    let baseline = from_json(&std::fs::read_to_string(path)?)?;
+    let regressions = compare(&baseline, &all, TOLERANCE);
+    for r in &regressions {
+        println!("regression: {r}");
+    }
+
+    Ok(regressions.is_empty())
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(env::args().skip(1)) {
+        Ok(a) => a,
+        Err(e) => {
+            eprintln!("{e}\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if !args.bench {
+        let mut ledger = None;
+        if args.submit {
+            match Ledger::load(&args.ledger) {
+                Ok(l) => ledger = Some(l),
+                Err(e) => {
+                    eprintln!("couldn't load ledger: {e:#}");
+                    return ExitCode::FAILURE;
+                }
+            }
+        }
+
+        let day = args.day.expect("checked in parse_args");
+        if let Some(out) = &args.render {
+            return match render(day, &args.params, out, args.scale) {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(e) => {
+                    eprintln!("couldn't render day {day}: {e}");
+                    ExitCode::FAILURE
+                }
+            };
+        }
+        return solve(day, &args.parts, &args.params, ledger.as_mut());
+    }
+
+    match run_bench(&args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("benchmark failed: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -1,8 +0,0 @@
-use aoc24_rs::{fetch_input, Day, Day11};
-
-fn main() {
-    println!(
-        "{:?}",
-        Day11::part2(&fetch_input(11).expect("fetched input"))
-    );
-}
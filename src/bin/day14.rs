@@ -1,6 +0,0 @@
-use aoc24_rs::{fetch_input, Day};
-
-fn main() {
-    let i = fetch_input(14).unwrap();
-    aoc24_rs::Day14::part2(&i);
-}
@@ -1,6 +0,0 @@
-use aoc24_rs::{fetch_input, Day, Day17};
-
-fn main() {
-    let input = fetch_input(17).unwrap();
-    assert_eq!("", Day17::part2(&input));
-}
@@ -1,12 +1,26 @@
 use std::{collections::HashMap, iter::zip};
 
-use crate::Day;
+use crate::{Day, Error};
 
 pub struct Day1 {}
 
 impl Day<i32> for Day1 {
+    type Params = ();
+
     fn part1(input: &str) -> i32 {
-        let (left, right) = parse(input);
+        Self::try_part1(input).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    fn part2(input: &str) -> i32 {
+        Self::try_part2(input).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    fn validate(input: &str) -> Option<Result<(), Error>> {
+        Some(parse(input).map(|_| ()))
+    }
+
+    fn try_part1(input: &str) -> Result<i32, Error> {
+        let (left, right) = parse(input)?;
 
         let mut sum = 0;
         for (a, b) in zip(left, right) {
@@ -16,11 +30,11 @@ impl Day<i32> for Day1 {
             sum += mag;
         }
 
-        sum
+        Ok(sum)
     }
 
-    fn part2(input: &str) -> i32 {
-        let (left, right) = parse(input);
+    fn try_part2(input: &str) -> Result<i32, Error> {
+        let (left, right) = parse(input)?;
 
         let freqs = right.iter().fold(HashMap::new(), |mut acc, v| {
             if let Some(freq) = acc.get_mut(v) {
@@ -32,29 +46,33 @@ impl Day<i32> for Day1 {
             acc
         });
 
-        left.iter().map(|v| v * freqs.get(v).unwrap_or(&0)).sum()
+        Ok(left.iter().map(|v| v * freqs.get(v).unwrap_or(&0)).sum())
     }
 }
 
-fn parse(input: &str) -> (Vec<i32>, Vec<i32>) {
+fn parse(input: &str) -> Result<(Vec<i32>, Vec<i32>), Error> {
     let mut a = vec![];
     let mut b = vec![];
 
-    input.trim().lines().for_each(|line| {
+    for line in input.trim().lines() {
         let ns: Vec<i32> = line
             .trim()
             .split_ascii_whitespace()
-            .map(|n| n.parse().expect("is a number"))
-            .collect();
+            .map(|n| n.parse().map_err(|_| Error::parse(1, input, n, "a number")))
+            .collect::<Result<_, _>>()?;
 
-        a.push(ns[0]);
-        b.push(ns[1]);
-    });
+        let &[left, right] = ns.as_slice() else {
+            return Err(Error::parse(1, input, line.trim(), "two numbers"));
+        };
+
+        a.push(left);
+        b.push(right);
+    }
 
     a.sort();
     b.sort();
 
-    (a, b)
+    Ok((a, b))
 }
 
 #[cfg(test)]
@@ -72,7 +90,27 @@ mod test {
 
         assert_eq!(
             super::parse(test_in),
-            (vec!(1, 2, 3, 3, 3, 4), vec!(3, 3, 3, 4, 5, 9))
+            Ok((vec!(1, 2, 3, 3, 3, 4), vec!(3, 3, 3, 4, 5, 9)))
         );
     }
+
+    #[test]
+    fn parse_errors() {
+        use crate::Error;
+
+        let e = super::parse("3   4\n4   x3\n").unwrap_err();
+        assert_eq!(
+            e,
+            Error::Parse {
+                day: 1,
+                line: 2,
+                column: 5,
+                token: "x3".to_string(),
+                expected: "a number"
+            }
+        );
+
+        let e = super::parse("3   4\n4\n").unwrap_err();
+        assert!(matches!(e, Error::Parse { line: 2, .. }));
+    }
 }
@@ -85,6 +85,8 @@ fn dropping_one(r: &[i8]) -> impl Iterator<Item = Vec<i8>> + '_ {
 pub struct Day2 {}
 
 impl Day<usize> for Day2 {
+    type Params = ();
+
     fn part1(input: &str) -> usize {
         parse(input).iter().filter(|r| report_is_safe(r)).count()
     }
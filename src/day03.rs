@@ -12,6 +12,8 @@ fn all_pat() -> Regex {
 pub struct Day3 {}
 
 impl Day<i64> for Day3 {
+    type Params = ();
+
     fn part1(input: &str) -> i64 {
         let pat = mul_pat();
 
@@ -25,6 +25,8 @@ fn gather(
 pub struct Day4 {}
 
 impl Day<i64> for Day4 {
+    type Params = ();
+
     fn part1(input: &str) -> i64 {
         let m = parse(input);
         let mut count = 0;
@@ -122,6 +122,8 @@ pub struct Day5 {
 }
 
 impl Day<i64> for Day5 {
+    type Params = ();
+
     fn part1(input: &str) -> i64 {
         let d = parse(input);
 
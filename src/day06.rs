@@ -106,6 +106,8 @@ impl Day6 {
 }
 
 impl Day<usize> for Day6 {
+    type Params = ();
+
     fn part1(input: &str) -> usize {
         let mut d = Day6::parse(input);
         while d.step(true).is_some() {}
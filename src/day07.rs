@@ -1,22 +1,29 @@
 use core::ops::Add;
 use std::ops::Mul;
 
-use crate::Day;
+use crate::{Day, Error};
 
 pub struct Day7 {}
 
-fn parse(input: &str) -> Vec<(i64, Vec<i64>)> {
+fn number(input: &str, n: &str) -> Result<i64, Error> {
+    n.parse().map_err(|_| Error::parse(7, input, n, "a number"))
+}
+
+fn parse(input: &str) -> Result<Vec<(i64, Vec<i64>)>, Error> {
     input
         .trim()
         .lines()
         .map(|line| {
-            let (a, bs) = line.trim().split_once(':').expect("has a colon");
+            let line = line.trim();
+            let (a, bs) = line
+                .split_once(':')
+                .ok_or_else(|| Error::parse(7, input, line, "'<answer>: <operands>'"))?;
             let bs = bs.split_whitespace();
 
-            (
-                a.parse().expect("is a number"),
-                bs.map(|b| b.parse().expect("is a number")).collect(),
-            )
+            Ok((
+                number(input, a)?,
+                bs.map(|b| number(input, b)).collect::<Result<_, _>>()?,
+            ))
         })
         .collect()
 }
@@ -71,19 +78,33 @@ fn count_solutions(answer: i64, operands: &[i64], operators: &[fn(i64, i64) -> i
 }
 
 impl Day<i64> for Day7 {
+    type Params = ();
+
     fn part1(input: &str) -> i64 {
+        Self::try_part1(input).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    fn part2(input: &str) -> i64 {
+        Self::try_part2(input).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    fn validate(input: &str) -> Option<Result<(), Error>> {
+        Some(parse(input).map(|_| ()))
+    }
+
+    fn try_part1(input: &str) -> Result<i64, Error> {
         let mut sum = 0;
 
-        for (answer, operands) in parse(input) {
+        for (answer, operands) in parse(input)? {
             if count_solutions(answer, &operands, &[add::<i64>, mul::<i64>]) > 0 {
                 sum += answer
             }
         }
 
-        sum
+        Ok(sum)
     }
 
-    fn part2(input: &str) -> i64 {
+    fn try_part2(input: &str) -> Result<i64, Error> {
         // let mut sum = 0;
 
         // for (answer, operands) in parse(input) {
@@ -94,7 +115,7 @@ impl Day<i64> for Day7 {
 
         // sum
 
-        parse(input)
+        Ok(parse(input)?
             .into_iter()
             .map(|(answer, operands)| {
                 if count_solutions(answer, &operands, &[add::<i64>, mul::<i64>, concat]) > 0 {
@@ -103,7 +124,7 @@ impl Day<i64> for Day7 {
                     0
                 }
             })
-            .sum()
+            .sum())
     }
 }
 
@@ -134,4 +155,13 @@ mod test {
         let a = Day7::part2(TEST_INPUT);
         assert_eq!(a, 11387);
     }
+
+    #[test]
+    fn bad_input() {
+        let e = Day7::try_part1("190: 10 19\n3267 81 40 27\n").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "day 7, line 2, column 1: expected '<answer>: <operands>', found \"3267 81 40 27\""
+        );
+    }
 }
@@ -1,6 +1,9 @@
 use std::collections::HashMap;
 
-use crate::Day;
+use crate::{
+    render::{self, Style, BLUE, YELLOW},
+    Day, Error, IntMap,
+};
 
 pub struct Day8 {
     map: HashMap<char, Vec<(i32, i32)>>,
@@ -99,17 +102,30 @@ fn gcd(a: i32, b: i32) -> i32 {
     a.abs()
 }
 
-#[allow(dead_code)]
-fn print_locs(d: &Day8, map: &HashMap<(i32, i32), char>) {
-    for y in 0..d.height {
-        for x in 0..d.width {
-            print!("{}", map.get(&(x, y)).unwrap_or(&'.'));
+/// Every antinode once resonant harmonics are taken into account.
+fn harmonics(info: &Day8) -> HashMap<(i32, i32), char> {
+    let mut antinodes = HashMap::<(i32, i32), char>::new();
+
+    for (freq, locs) in info.map.iter() {
+        for (left, right) in cartesian_product(locs) {
+            // println!("{:?} -> {:?}", left, right);
+            let dests = antinode_positions_part2(info, left, right);
+            for dest in dests {
+                antinodes.insert(dest, *freq);
+            }
         }
-        println!();
     }
+
+    antinodes
+}
+
+fn co(&(x, y): &(i32, i32)) -> (isize, isize) {
+    (x as isize, y as isize)
 }
 
 impl Day<i32> for Day8 {
+    type Params = ();
+
     fn part1(input: &str) -> i32 {
         let info = parse(input);
 
@@ -124,7 +140,6 @@ impl Day<i32> for Day8 {
             }
         }
 
-        // print_locs(&info, &antinodes);
         // dbg!(&antinodes);
         // println!("w {} h {}", &info.width, &info.height);
 
@@ -133,24 +148,27 @@ impl Day<i32> for Day8 {
 
     fn part2(input: &str) -> i32 {
         let info = parse(input);
+        harmonics(&info).keys().count() as i32
+    }
 
-        let mut antinodes = HashMap::<(i32, i32), char>::new();
-
-        for (freq, locs) in info.map.iter() {
-            for (left, right) in cartesian_product(locs) {
-                // println!("{:?} -> {:?}", left, right);
-                let dests = antinode_positions_part2(&info, left, right);
-                for dest in dests {
-                    antinodes.insert(dest, *freq);
-                }
-            }
-        }
-
-        // print_locs(&info, &antinodes);
-        // dbg!(&antinodes);
-        // println!("w {} h {}", &info.width, &info.height);
-
-        antinodes.keys().count() as i32
+    /// The antennas, and the part 2 antinodes as `#`.
+    fn render(input: &str, _params: &()) -> Option<Result<IntMap<Style>, Error>> {
+        let info = parse(input);
+        let antinodes = harmonics(&info)
+            .into_keys()
+            .map(|p| (co(&p), Style::plain('#').fg(BLUE)));
+        let mut m = render::sparse(
+            info.width as usize,
+            info.height as usize,
+            Style::default(),
+            antinodes,
+        );
+        m.overlay(info.map.iter().flat_map(|(&freq, locs)| {
+            locs.iter()
+                .map(move |p| (co(p), Style::plain(freq).fg(YELLOW)))
+        }));
+
+        Some(Ok(m))
     }
 }
 
@@ -182,4 +200,25 @@ mod test {
     fn part2() {
         assert_eq!(Day8::part2(TEST_INPUT), 34);
     }
+
+    #[test]
+    fn render_t() {
+        let m = Day8::render(TEST_INPUT, &()).unwrap().unwrap();
+        assert_eq!(
+            "##....#....#
+.#.#....0...
+..#.#0....#.
+..##...0....
+....0....#..
+.#...#A....#
+...#..#.....
+#....#.#....
+..#.....A...
+....#....A..
+.#........#.
+...#......##
+",
+            m.ascii()
+        );
+    }
 }
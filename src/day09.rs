@@ -140,6 +140,8 @@ fn checksum2(disk: &[Packet]) -> u64 {
 }
 
 impl Day<u64> for Day9 {
+    type Params = ();
+
     fn part1(input: &str) -> u64 {
         let d = expand_map(input);
         let d = repack(d);
@@ -1,79 +1,13 @@
-use crate::Day;
+use crate::{intmap::IntMap, Day};
 
 pub struct Day10 {}
 
-type Pt = (i64, i64);
+type Pt = (isize, isize);
 
-struct Map {
-    width: i64,
-    height: i64,
-    cells: Vec<i64>,
-}
-
-impl Map {
-    fn pt_to_idx(&self, p: Pt) -> usize {
-        (p.1 * self.width + p.0) as usize
-    }
-
-    // fn idx_to_pt(&self, idx: usize) -> Pt {
-    //     let x = idx as i64 % self.width;
-    //     let y = idx as i64 / self.height;
-
-    //     (x, y)
-    // }
-
-    fn in_bounds(&self, p: Pt) -> bool {
-        0 <= p.0 && p.0 < self.width && 0 <= p.1 && p.1 < self.height
-    }
-
-    fn get(&self, p: Pt) -> Option<i64> {
-        if !self.in_bounds(p) {
-            return None;
-        }
-        Some(self.cells[self.pt_to_idx(p)])
-    }
-
-    // fn put(&mut self, p: Pt, val: i64) -> Option<i64> {
-    //     if !self.in_bounds(p) {
-    //         return None;
-    //     }
-    //     let id = self.pt_to_idx(p);
-    //     self.cells[id] = val;
-    //     Some(val)
-    // }
-
-    fn all_pts(&self) -> Vec<Pt> {
-        let mut outs = vec![];
-        for x in 0..self.width {
-            for y in 0..self.width {
-                outs.push((x, y))
-            }
-        }
-        outs
-    }
-}
+type Map = IntMap<i64>;
 
 fn parse_map(input: &str) -> Map {
-    let mut width: i64 = 0;
-
-    let cells: Vec<i64> = input
-        .trim()
-        .lines()
-        .enumerate()
-        .flat_map(|(i, line)| {
-            width = ((i as i64) + 1).max(width);
-            line.trim()
-                .chars()
-                .map(|v| v.to_digit(10).expect("not a digit") as i64)
-        })
-        .collect();
-
-    let height = cells.len() as i64 / width;
-    Map {
-        width,
-        height,
-        cells,
-    }
+    IntMap::from_raw(input, |v| v.to_digit(10).expect("not a digit") as i64)
 }
 
 fn reachable(m: &Map, zero: Pt, nine: Pt) -> bool {
@@ -86,14 +20,10 @@ fn reachable(m: &Map, zero: Pt, nine: Pt) -> bool {
         if curr == nine {
             return true;
         }
-        let here = m.get(curr).unwrap();
+        let here = m[curr];
 
-        for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
-            let co = (curr.0 + dx, curr.1 + dy);
-            if !m.in_bounds(co) {
-                continue;
-            }
-            if m.get(co) == Some(here + 1) {
+        for co in m.neighbours4(curr) {
+            if m[co] == here + 1 {
                 open.insert(co);
             }
         }
@@ -111,17 +41,13 @@ fn reachable(m: &Map, zero: Pt, nine: Pt) -> bool {
 
 fn solve_a(m: &Map) -> i64 {
     let zeroes = m
-        .all_pts()
         .iter()
-        .map(|&pt| (pt, m.get(pt).unwrap()))
-        .filter(|(_, v)| *v == 0)
+        .filter(|(_, &v)| v == 0)
         .map(|(a, _)| a)
         .collect::<Vec<_>>();
     let nines = m
-        .all_pts()
         .iter()
-        .map(|&pt| (pt, m.get(pt).unwrap()))
-        .filter(|(_, v)| *v == 9)
+        .filter(|(_, &v)| v == 9)
         .map(|(a, _)| a)
         .collect::<Vec<_>>();
 
@@ -140,39 +66,30 @@ fn solve_a(m: &Map) -> i64 {
 }
 
 fn solve_b(m: &Map) -> i64 {
-    let mut counts: Vec<_> = m
-        .cells
-        .iter()
-        .map(|&v| if v == 9 { 1 } else { 0 })
-        .collect();
+    let mut counts = m.map(|&v| if v == 9 { 1 } else { 0 });
 
     for i in (1..=9).rev() {
-        for p in m.all_pts() {
-            if m.get(p).unwrap() != (i - 1) {
+        for p in m.coords() {
+            if m[p] != (i - 1) {
                 continue;
             }
-            let iter = [(-1, 0), (1, 0), (0, -1), (0, 1)].iter().map(|(dx, dy)| {
-                let co = (p.0 + dx, p.1 + dy);
-                if m.get(co) == Some(i) {
-                    let idx = m.pt_to_idx(co);
-                    counts[idx]
-                } else {
-                    0
-                }
-            });
-
-            counts[m.pt_to_idx(p)] = iter.sum();
+            counts[p] = m
+                .neighbours4(p)
+                .filter(|&co| m[co] == i)
+                .map(|co| counts[co])
+                .sum();
         }
     }
 
-    m.all_pts()
-        .into_iter()
-        .filter(|&p| m.get(p) == Some(0))
-        .map(|p| counts[m.pt_to_idx(p)])
+    m.iter()
+        .filter(|(_, &v)| v == 0)
+        .map(|(p, _)| counts[p])
         .sum()
 }
 
 impl Day<i64> for Day10 {
+    type Params = ();
+
     fn part1(input: &str) -> i64 {
         solve_a(&parse_map(input))
     }
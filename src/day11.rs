@@ -1,9 +1,16 @@
 use std::collections::HashMap;
 
-use crate::Day;
+use crate::{params, Day, Error};
 
 pub struct Day11 {}
 
+params! {
+    pub struct Day11Params {
+        pub blinks1: u64 = 25,
+        pub blinks2: u64 = 75,
+    }
+}
+
 type Stone = u64;
 
 #[inline(never)]
@@ -100,29 +107,35 @@ fn parse(input: &str) -> Vec<Stone> {
         .collect()
 }
 
+fn blink(input: &str, blinks: u64) -> u64 {
+    let v = parse(input);
+    let mut cache = HashMap::new();
+    let sum: u64 = v
+        .into_iter()
+        .map(|val| count_splits(val, blinks, &mut cache))
+        .sum();
+    // v.len() as u64
+    // println!("{} vals cached", cache.len());
+    sum
+}
+
 impl Day<u64> for Day11 {
+    type Params = Day11Params;
+
     fn part1(input: &str) -> u64 {
-        let v = parse(input);
-        let mut cache = HashMap::new();
-        let sum: u64 = v
-            .into_iter()
-            .map(|val| count_splits(val, 25, &mut cache))
-            .sum();
-        // v.len() as u64
-        // println!("{} vals cached", cache.len());
-        sum
+        blink(input, Day11Params::default().blinks1)
     }
 
     fn part2(input: &str) -> u64 {
-        let v = parse(input);
-        let mut cache = HashMap::new();
-        let sum: u64 = v
-            .into_iter()
-            .map(|val| count_splits(val, 75, &mut cache))
-            .sum();
-        // v.len() as u64
-        // println!("{} vals cached", cache.len());
-        sum
+        blink(input, Day11Params::default().blinks2)
+    }
+
+    fn try_part1_with(input: &str, params: &Day11Params) -> Result<u64, Error> {
+        Ok(blink(input, params.blinks1))
+    }
+
+    fn try_part2_with(input: &str, params: &Day11Params) -> Result<u64, Error> {
+        Ok(blink(input, params.blinks2))
     }
 }
 
@@ -191,6 +191,8 @@ fn score2(map: &Map) -> i64 {
 pub struct Day12 {}
 
 impl Day<i64> for Day12 {
+    type Params = ();
+
     fn part1(input: &str) -> i64 {
         score1(&parse(input))
     }
@@ -1,15 +1,6 @@
 use nalgebra::{Matrix2, Vector2};
-use regex::{Captures, Regex};
 
-fn button_pat() -> Regex {
-    Regex::new(r"Button (A|B): X\+(\d+), Y\+(\d+)").expect("regex problem")
-}
-
-fn prize_pat() -> Regex {
-    Regex::new(r"Prize: X=(\d+), Y=(\d+)").expect("prize regex broken")
-}
-
-use crate::Day;
+use crate::{parse, Day, Error};
 
 #[derive(Debug, PartialEq)]
 struct Machine {
@@ -17,43 +8,29 @@ struct Machine {
     prize: Vector2<i64>,
 }
 
-fn get_num(a: &Captures, i: usize) -> i64 {
-    a.get(i)
-        .unwrap()
-        .as_str()
-        .parse()
-        .expect("matches a number")
-}
-
-fn parse(input: &str) -> Vec<Machine> {
-    let pat = button_pat();
-    let prize_p = prize_pat();
-
-    input
-        .trim()
-        .split("\n\n")
+fn parse(input: &str) -> Result<Vec<Machine>, Error> {
+    parse::sections(input)
+        .into_iter()
         .map(|section| {
-            let lines: Vec<&str> = section.trim().lines().collect();
-
-            let a = pat
-                .captures(lines[0])
-                .expect("first line should match button");
-            let b = pat
-                .captures(lines[1])
-                .expect("second line should match button");
-            let p = prize_p
-                .captures(lines[2])
-                .expect("third line should match prize");
-
-            Machine {
-                buttons: Matrix2::<i64>::new(
-                    get_num(&a, 2),
-                    get_num(&b, 2),
-                    get_num(&a, 3),
-                    get_num(&b, 3),
-                ),
-                prize: Vector2::new(get_num(&p, 1), get_num(&p, 2)),
-            }
+            let mut lines = parse::lines(section);
+            let mut line = |shape: &str, expected| {
+                let l = lines.next().ok_or(Error::truncated(13, expected))?;
+                if parse::skeleton(l) != shape {
+                    return Err(Error::parse(13, input, l, expected));
+                }
+                let n = parse::ints::<i64>(l)
+                    .map_err(|t| Error::parse(13, input, t, "a 64-bit number"))?;
+                Ok(Vector2::new(n[0], n[1]))
+            };
+
+            let a = line("Button A: X+#, Y+#", "'Button A: X+<n>, Y+<n>'")?;
+            let b = line("Button B: X+#, Y+#", "'Button B: X+<n>, Y+<n>'")?;
+            let p = line("Prize: X=#, Y=#", "'Prize: X=<n>, Y=<n>'")?;
+
+            Ok(Machine {
+                buttons: Matrix2::from_columns(&[a, b]),
+                prize: p,
+            })
         })
         .collect()
 }
@@ -88,8 +65,22 @@ fn solve_machine(machine: &Machine, offset: Vector2<i64>) -> Option<Vector2<i64>
 pub struct Day13 {}
 
 impl Day<i64> for Day13 {
+    type Params = ();
+
     fn part1(input: &str) -> i64 {
-        let machines = parse(input);
+        Self::try_part1(input).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    fn part2(input: &str) -> i64 {
+        Self::try_part2(input).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    fn validate(input: &str) -> Option<Result<(), Error>> {
+        Some(parse(input).map(|_| ()))
+    }
+
+    fn try_part1(input: &str) -> Result<i64, Error> {
+        let machines = parse(input)?;
 
         let mut winnable: Vec<(Machine, Vector2<i64>)> = vec![];
         for machine in machines {
@@ -115,14 +106,14 @@ impl Day<i64> for Day13 {
             // }
         }
 
-        winnable
+        Ok(winnable
             .iter()
             .map(|(_m, input)| input.x * 3 + input.y)
-            .sum()
+            .sum())
     }
 
-    fn part2(input: &str) -> i64 {
-        let machines = parse(input);
+    fn try_part2(input: &str) -> Result<i64, Error> {
+        let machines = parse(input)?;
         let offset = Vector2::new(10000000000000, 10000000000000);
 
         let mut winnable: Vec<(Machine, Vector2<i64>)> = vec![];
@@ -132,10 +123,10 @@ impl Day<i64> for Day13 {
             }
         }
 
-        winnable
+        Ok(winnable
             .iter()
             .map(|(_m, input)| input.x * 3 + input.y)
-            .sum()
+            .sum())
     }
 }
 
@@ -163,14 +154,14 @@ mod test {
 
     #[test]
     fn t_parse() {
-        let machines = parse(TEST_INPUT);
+        let machines = parse(TEST_INPUT).unwrap();
         println!("{:?}", machines);
         assert_eq!(machines.len(), 4);
     }
 
     #[test]
     fn t_machine_1() {
-        let ms = parse(TEST_INPUT);
+        let ms = parse(TEST_INPUT).unwrap();
         let m = &ms[0];
 
         // v represents (button_a_presses, button_b_presses)
@@ -181,6 +172,32 @@ mod test {
         assert_eq!(r, m.prize);
     }
 
+    #[test]
+    fn t_parse_errors() {
+        let truncated = &TEST_INPUT[..TEST_INPUT.find("Button B: X+67").unwrap()];
+        assert_eq!(
+            Err(Error::truncated(13, "'Button B: X+<n>, Y+<n>'")),
+            parse(truncated)
+        );
+
+        let mangled = TEST_INPUT.replace("Prize: X=7870", "Prize X=7870");
+        assert!(matches!(
+            parse(&mangled),
+            Err(Error::Parse { line: 12, .. })
+        ));
+
+        let huge = TEST_INPUT.replace("X=8400", "X=99999999999999999999");
+        assert!(matches!(
+            parse(&huge),
+            Err(Error::Parse {
+                line: 4,
+                column: 14,
+                expected: "a 64-bit number",
+                ..
+            })
+        ));
+    }
+
     #[test]
     fn t_part1() {
         assert_eq!(480, Day13::part1(TEST_INPUT));
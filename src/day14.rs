@@ -1,9 +1,13 @@
-use std::cmp::Ordering;
+use std::{cmp::Ordering, collections::HashSet};
 
-use crate::{iterext::IterExt, Day};
+use crate::{
+    iterext::IterExt,
+    params, parse,
+    render::{self, Style, GREEN},
+    Day, Error, IntMap, Point,
+};
 
 use nalgebra::Vector2;
-use regex::Regex;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 struct Robot {
@@ -11,38 +15,139 @@ struct Robot {
     v: Vector2<i64>,
 }
 
-fn parse(input: &str) -> Vec<Robot> {
-    let pat = Regex::new(r"p=(?<px>[-\d]+),(?<py>[-\d]+) v=(?<vx>[-\d]+),(?<vy>[-\d]+)")
-        .expect("regex error");
+fn parse(input: &str) -> Result<Vec<Robot>, Error> {
+    parse::lines(input)
+        .map(|line| {
+            if parse::skeleton(line) != "p=#,# v=#,#" {
+                return Err(Error::parse(14, input, line, "'p=<x>,<y> v=<dx>,<dy>'"));
+            }
+            let n = parse::ints::<i64>(line).map_err(|t| Error::parse(14, input, t, "a number"))?;
 
-    pat.captures_iter(input)
-        .map(|m| Robot {
-            p: Vector2::new(m["px"].parse().unwrap(), m["py"].parse().unwrap()),
-            v: Vector2::new(m["vx"].parse().unwrap(), m["vy"].parse().unwrap()),
+            Ok(Robot {
+                p: Vector2::new(n[0], n[1]),
+                v: Vector2::new(n[2], n[3]),
+            })
         })
         .collect()
 }
 
+/// Where `r` is after `n` seconds (which may be negative).
 fn step(r: Robot, world_size: (i64, i64), n: i64) -> Robot {
-    let mut r = r;
+    let p = r.p + r.v * n;
+    Robot {
+        p: Vector2::new(p.x.rem_euclid(world_size.0), p.y.rem_euclid(world_size.1)),
+        v: r.v,
+    }
+}
 
-    for _ in 0..n {
-        r.p += r.v;
-        if r.p.x < 0 {
-            r.p.x += world_size.0;
-        }
-        if r.p.y < 0 {
-            r.p.y += world_size.1;
+fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 {
+        a.abs()
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Every robot in a world of a given size, as they were at time zero.
+#[derive(Clone, Debug)]
+pub struct Swarm {
+    robots: Vec<Robot>,
+    width: i64,
+    height: i64,
+}
+
+impl Swarm {
+    pub fn new(input: &str, width: i64, height: i64) -> Result<Swarm, Error> {
+        if width <= 0 || height <= 0 {
+            return Err(Error::runtime(
+                14,
+                format!("the floor must be at least 1x1, not {width}x{height}"),
+            ));
         }
-        if r.p.x >= world_size.0 {
-            r.p.x -= world_size.0;
+        Ok(Swarm {
+            robots: parse(input)?,
+            width,
+            height,
+        })
+    }
+
+    fn world_size(&self) -> (i64, i64) {
+        (self.width, self.height)
+    }
+
+    fn robots_at(&self, t: i64) -> Vec<Robot> {
+        self.robots
+            .iter()
+            .map(|&r| step(r, self.world_size(), t))
+            .collect()
+    }
+
+    /// Every robot's position after `t` seconds.
+    pub fn at(&self, t: i64) -> Vec<Point> {
+        self.robots_at(t)
+            .iter()
+            .map(|r| Point::new(r.p.x as isize, r.p.y as isize))
+            .collect()
+    }
+
+    /// How long until every robot is back where it started, whatever its
+    /// velocity: the lcm of the width and height, which for the real
+    /// (prime-sized) world is just width × height.
+    pub fn period(&self) -> i64 {
+        self.width / gcd(self.width, self.height) * self.height
+    }
+
+    /// Every frame in the period, `1..=period`, scored against the rest.
+    pub fn scores(&self) -> Vec<FrameScore> {
+        let mut frames: Vec<FrameScore> = (1..=self.period())
+            .map(|t| measure(t, &self.robots_at(t), self.world_size()))
+            .collect();
+
+        let metrics: [fn(&FrameScore) -> f64; 5] = [
+            |f| -f.var_x,
+            |f| -f.var_y,
+            |f| -(f.safety as f64),
+            |f| f.neighbours as f64,
+            |f| f.run as f64,
+        ];
+        for m in metrics {
+            let n = frames.len() as f64;
+            let mean = frames.iter().map(m).sum::<f64>() / n;
+            let sd = variance(frames.iter().map(m)).sqrt();
+            if sd > 0.0 {
+                for f in frames.iter_mut() {
+                    f.anomaly += (m(f) - mean) / sd;
+                }
+            }
         }
-        if r.p.y >= world_size.1 {
-            r.p.y -= world_size.1;
+
+        frames
+    }
+
+    /// The frame that stands out most from the rest of the period, as long
+    /// as its lead over the runner-up is more than `lead` times the
+    /// runner-up's over an average frame (which scores 0). A picture leaves
+    /// everything else far behind; noise has a best frame too, but only
+    /// just.
+    pub fn most_ordered(&self, lead: f64) -> Option<FrameScore> {
+        let mut scores = self.scores();
+        scores.sort_by(|a, b| b.anomaly.total_cmp(&a.anomaly));
+        match scores[..] {
+            [ref best, ref runner_up, ..]
+                if best.anomaly - runner_up.anomaly > lead * runner_up.anomaly =>
+            {
+                Some(best.clone())
+            }
+            _ => None,
         }
     }
 
-    r
+    /// The first time in `1..=horizon` at which `pred` holds for the
+    /// robots' positions. Looking past the period is pointless, so the
+    /// horizon is capped there.
+    pub fn find(&self, horizon: i64, mut pred: impl FnMut(&[Point]) -> bool) -> Option<i64> {
+        (1..=horizon.min(self.period())).find(|&t| pred(&self.at(t)))
+    }
 }
 
 fn quadrant(r: &Robot, world_size: (i64, i64)) -> Option<i64> {
@@ -61,129 +166,153 @@ fn quadrant(r: &Robot, world_size: (i64, i64)) -> Option<i64> {
     qx.and_then(|vx| qy.map(|vy| vy * 2 + vx))
 }
 
-fn has_span(rs: &[Robot], world_size: (i64, i64), threshold: i64) -> bool {
-    let mut sorted = rs.to_owned();
-    sorted.sort_by_key(|r| r.p.y * world_size.0 + r.p.x);
-    let diffs = sorted.as_slice().pairs().map(|(a, b)| {
-        let dxy = (a.p - b.p).abs();
-        dxy.y * world_size.0 + dxy.x
-    });
-
-    let mut span = 0;
-
-    for d in diffs {
-        if d == 1 {
-            span += 1;
-            if span >= threshold {
-                return true;
-            }
-        } else {
-            span = 0;
-        }
-    }
+/// How orderly the robots look at one moment.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FrameScore {
+    pub t: i64,
+    pub var_x: f64,
+    pub var_y: f64,
+    /// Part 1's product of the quadrant counts, which drops when the
+    /// robots bunch up in one part of the floor.
+    pub safety: i64,
+    /// How many robots have another directly beside, above or below them.
+    pub neighbours: usize,
+    /// The longest unbroken horizontal line of robots.
+    pub run: usize,
+    /// How far this frame stands out from the others in the period: each
+    /// metric's z-score, signed so that more order is higher, summed.
+    pub anomaly: f64,
+}
 
-    false
+fn variance(xs: impl Iterator<Item = f64> + Clone) -> f64 {
+    let n = xs.clone().count().max(1) as f64;
+    let mean = xs.clone().sum::<f64>() / n;
+    xs.map(|x| (x - mean).powi(2)).sum::<f64>() / n
+}
 
-    // use std::collections::HashSet;
-    // let mut map = HashSet::<Vector2<i64>>::new();
-    // for r in rs {
-    //     map.insert(r.p);
-    // }
+fn safety_factor(rs: &[Robot], world_size: (i64, i64)) -> i64 {
+    let mut counts = [0; 4];
+    for r in rs {
+        if let Some(q) = quadrant(r, world_size) {
+            counts[q as usize] += 1;
+        }
+    }
 
-    // let mut current_span = 0;
+    counts[0] * counts[1] * counts[2] * counts[3]
+}
 
-    // for y in 0..world_size.1 {
-    //     for x in 0..world_size.0 {
-    //         if map.contains(&Vector2::new(x, y)) {
-    //             current_span += 1;
-    //             if current_span > threshold {
-    //                 return true;
-    //             }
-    //         } else {
-    //             current_span = 0;
-    //         };
-    //     }
-    // }
+/// Everything but the anomaly, which needs the rest of the period.
+fn measure(t: i64, rs: &[Robot], world_size: (i64, i64)) -> FrameScore {
+    let mut cells: Vec<(i64, i64)> = rs.iter().map(|r| (r.p.y, r.p.x)).collect();
+    cells.sort();
+    cells.dedup();
+    let occupied: HashSet<_> = cells.iter().copied().collect();
+
+    let neighbours = cells
+        .iter()
+        .filter(|&&(y, x)| {
+            [(y - 1, x), (y + 1, x), (y, x - 1), (y, x + 1)]
+                .iter()
+                .any(|n| occupied.contains(n))
+        })
+        .count();
 
-    // false
-}
+    let mut run = cells.len().min(1);
+    let mut current = run;
+    for (a, b) in cells.as_slice().pairs() {
+        current = if a.0 == b.0 && b.1 - a.1 == 1 {
+            current + 1
+        } else {
+            1
+        };
+        run = run.max(current);
+    }
 
-#[allow(dead_code)]
-fn print_map(rs: &Vec<Robot>, world_size: (i64, i64), output: bool) -> i64 {
-    use std::collections::HashSet;
-    let mut map = HashSet::<Vector2<i64>>::new();
-    for r in rs {
-        map.insert(r.p);
-    }
-
-    let mut longest_span = 0;
-    let mut current_span = 0;
-
-    for y in 0..world_size.1 {
-        for x in 0..world_size.0 {
-            let c = if map.contains(&Vector2::new(x, y)) {
-                current_span += 1;
-                '#'
-            } else {
-                longest_span = longest_span.max(current_span);
-                current_span = 0;
-                '.'
-            };
-            if output {
-                print!("{}", c);
-            }
-        }
-        if output {
-            println!();
-        }
+    FrameScore {
+        t,
+        var_x: variance(rs.iter().map(|r| r.p.x as f64)),
+        var_y: variance(rs.iter().map(|r| r.p.y as f64)),
+        safety: safety_factor(rs, world_size),
+        neighbours,
+        run,
+        anomaly: 0.0,
     }
+}
 
-    longest_span
+/// The robots as `#`s on an otherwise empty floor.
+fn picture(ps: &[Point], world_size: (i64, i64)) -> IntMap<Style> {
+    let robots = ps.iter().map(|&p| (p, Style::plain('#').fg(GREEN)));
+    render::sparse(
+        world_size.0 as usize,
+        world_size.1 as usize,
+        Style::default(),
+        robots,
+    )
 }
 
 pub struct Day14 {}
 
+params! {
+    pub struct Day14Params {
+        pub width: i64 = 101,
+        pub height: i64 = 103,
+        /// How long part 1 lets the robots move for.
+        pub seconds: i64 = 100,
+        /// How far the tree's frame must stand out, as a multiple of how far
+        /// the runner-up does.
+        pub lead: f64 = 1.0,
+    }
+}
+
 impl Day<i64> for Day14 {
-    fn part1(input: &str) -> i64 {
-        let rs = parse(input);
-        let world_size = (101, 103);
-
-        let rs = rs.iter().map(|&r| step(r, world_size, 100));
-        let mut counts = [0; 4];
-        for r in rs {
-            if let Some(q) = quadrant(&r, world_size) {
-                counts[q as usize] += 1;
-            }
-        }
+    type Params = Day14Params;
 
-        counts[0] * counts[1] * counts[2] * counts[3]
+    fn part1(input: &str) -> i64 {
+        Self::try_part1(input).unwrap_or_else(|e| panic!("{e}"))
     }
 
     fn part2(input: &str) -> i64 {
-        let mut rs = parse(input);
-        let world_size = (101, 103);
-
-        let mut steps = 0;
-        loop {
-            steps += 1;
-            let rs_: Vec<Robot> = rs.iter().map(|&r| step(r, world_size, 1)).collect();
-            // let mut counts = [0; 4];
-            // for r in &rs_ {
-            //     if let Some(q) = quadrant(r, world_size) {
-            //         counts[q as usize] += 1;
-            //     }
-            // }
-
-            // println!("tree on {}", steps);
-            if has_span(&rs_, world_size, 10) {
-                // print_map(&rs_, world_size, true);
-                break;
-            }
+        Self::try_part2(input).unwrap_or_else(|e| panic!("{e}"))
+    }
 
-            rs = rs_;
-        }
+    fn validate(input: &str) -> Option<Result<(), Error>> {
+        Some(parse(input).map(|_| ()))
+    }
+
+    fn try_part1(input: &str) -> Result<i64, Error> {
+        Self::try_part1_with(input, &Day14Params::default())
+    }
+
+    fn try_part2(input: &str) -> Result<i64, Error> {
+        Self::try_part2_with(input, &Day14Params::default())
+    }
+
+    fn try_part1_with(input: &str, params: &Day14Params) -> Result<i64, Error> {
+        let swarm = Swarm::new(input, params.width, params.height)?;
+        let rs = swarm.robots_at(params.seconds);
+
+        Ok(safety_factor(&rs, swarm.world_size()))
+    }
+
+    fn try_part2_with(input: &str, params: &Day14Params) -> Result<i64, Error> {
+        let swarm = Swarm::new(input, params.width, params.height)?;
+
+        let best = swarm
+            .most_ordered(params.lead)
+            .ok_or_else(|| Error::no_answer(14, "no frame in the period looks like a tree"))?;
 
-        steps
+        Ok(best.t)
+    }
+
+    /// The robots at the moment they draw the tree.
+    fn render(input: &str, params: &Day14Params) -> Option<Result<IntMap<Style>, Error>> {
+        let draw = || {
+            let seconds = Self::try_part2_with(input, params)?;
+            let swarm = Swarm::new(input, params.width, params.height)?;
+            Ok(picture(&swarm.at(seconds), swarm.world_size()))
+        };
+
+        Some(draw())
     }
 }
 
@@ -206,8 +335,77 @@ mod test {
 
     #[test]
     fn parse_t() {
-        let rs = super::parse(TEST_INPUT);
+        let rs = super::parse(TEST_INPUT).unwrap();
         assert_eq!(rs.len(), 12);
+
+        let bad = TEST_INPUT.replace("v=2,3", "v=2;3");
+        assert!(matches!(
+            super::parse(&bad),
+            Err(Error::Parse {
+                line: 9,
+                column: 9,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn part1_sample() {
+        let params = Day14Params {
+            width: 11,
+            height: 7,
+            ..Default::default()
+        };
+        assert_eq!(Ok(12), Day14::try_part1_with(TEST_INPUT, &params));
+    }
+
+    #[test]
+    fn picture_t() {
+        let swarm = Swarm::new(TEST_INPUT, 11, 7).unwrap();
+        assert_eq!(
+            "......#..#.
+...........
+#..........
+.##........
+.....#.....
+...##......
+.#....#....
+",
+            picture(&swarm.at(100), (11, 7)).ascii()
+        );
+    }
+
+    #[test]
+    fn swarm_t() {
+        let swarm = Swarm::new(TEST_INPUT, 11, 7).unwrap();
+        assert_eq!(77, swarm.period());
+        assert_eq!(swarm.at(0), swarm.at(swarm.period()));
+        assert_eq!(swarm.at(-1), swarm.at(swarm.period() - 1));
+        assert_ne!(swarm.at(0), swarm.at(1));
+
+        assert_eq!(Some(1), swarm.find(100, |_| true));
+        // the first robot's steps are coprime to both sides, so it takes
+        // the whole period to come back
+        assert_eq!(Some(77), swarm.find(1000, |ps| ps[0] == Point::new(0, 4)));
+        assert_eq!(None, swarm.find(i64::MAX, |_| false));
+
+        assert_eq!(
+            Some(Error::runtime(
+                14,
+                "the floor must be at least 1x1, not 0x7"
+            )),
+            Swarm::new(TEST_INPUT, 0, 7).err()
+        );
+
+        let params = Day14Params {
+            width: 11,
+            height: 7,
+            ..Default::default()
+        };
+        assert!(matches!(
+            Day14::try_part2_with(TEST_INPUT, &params),
+            Err(Error::NoAnswer { day: 14, .. })
+        ));
     }
 
     #[test]
@@ -235,4 +433,71 @@ mod test {
         r.p.x = 5;
         assert_eq!(quadrant(&r, (11, 7)), None);
     }
+
+    /// Robots that draw a framed tree at time `t` in the full-sized world,
+    /// with as many again wandering about at random.
+    fn hidden_tree(t: i64) -> String {
+        let mut next = crate::test::lcg(0x2024);
+        let mut rand = |n: i64| next() as i64 % n;
+
+        let mut picture = vec![];
+        for i in 0..31 {
+            picture.extend([(30 + i, 20), (30 + i, 52), (30, 21 + i), (60, 21 + i)]);
+        }
+        for row in 0..14 {
+            for dx in -row..=row {
+                picture.push((45 + dx, 24 + row * 2));
+            }
+        }
+
+        let mut out = String::new();
+        for (x, y) in picture.clone() {
+            let (vx, vy) = (rand(201) - 100, rand(205) - 102);
+            let (px, py) = ((x - vx * t).rem_euclid(101), (y - vy * t).rem_euclid(103));
+            out += &format!("p={px},{py} v={vx},{vy}\n");
+        }
+        for _ in 0..picture.len() {
+            let (px, py) = (rand(101), rand(103));
+            let (vx, vy) = (rand(201) - 100, rand(205) - 102);
+            out += &format!("p={px},{py} v={vx},{vy}\n");
+        }
+        out
+    }
+
+    #[test]
+    fn finds_hidden_tree() {
+        let input = hidden_tree(6512);
+        let swarm = Swarm::new(&input, 101, 103).unwrap();
+        let scores = swarm.scores();
+        assert_eq!(101 * 103, scores.len());
+
+        let best = scores
+            .iter()
+            .max_by(|a, b| a.anomaly.total_cmp(&b.anomaly))
+            .unwrap();
+        assert_eq!(6512, best.t);
+        assert_eq!(31, best.run);
+        let runner_up = scores
+            .iter()
+            .filter(|f| f.t != best.t)
+            .map(|f| f.anomaly)
+            .fold(f64::MIN, f64::max);
+        assert!(best.anomaly - runner_up > runner_up);
+
+        // the lead needed decides between the tree and no answer at all
+        let ratio = (best.anomaly - runner_up) / runner_up;
+        assert_eq!(Some(best.clone()), swarm.most_ordered(ratio * 0.99));
+        assert_eq!(None, swarm.most_ordered(ratio * 1.01));
+
+        let params = Day14Params::default();
+        assert_eq!(Ok(6512), Day14::try_part2_with(&input, &params));
+        let params = Day14Params {
+            lead: ratio * 1.01,
+            ..Default::default()
+        };
+        assert!(matches!(
+            Day14::try_part2_with(&input, &params),
+            Err(Error::NoAnswer { day: 14, .. })
+        ));
+    }
 }
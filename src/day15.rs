@@ -1,8 +1,12 @@
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 
 use nalgebra::Vector2;
 
-use crate::Day;
+use crate::{
+    parse,
+    render::{self, Style, GREY, RED, WHITE, YELLOW},
+    Day, Dir4, Error, IntMap, Point,
+};
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Copy, Clone)]
 enum Object {
@@ -16,13 +20,18 @@ enum Object {
 type Co = Vector2<i64>;
 type Map = HashMap<Co, Object>;
 
-fn parse(input: &str) -> (Map, Co, Vec<Co>) {
-    let (map_section, move_section) = input.split_once("\n\n").expect("didn't find a blank line");
+fn parse(input: &str) -> Result<(Map, Co, Vec<Dir4>), Error> {
+    let [map_section, move_section, ref extra @ ..] = parse::sections(input)[..] else {
+        return Err(Error::truncated(15, "a blank line between map and moves"));
+    };
+    if let Some(extra) = extra.first().and_then(|s| s.lines().next()) {
+        return Err(Error::parse(15, input, extra, "the end of the input"));
+    }
 
     let mut map = Map::new();
-    let mut robotpos = Co::zeros();
-    for (y, line) in map_section.trim().lines().enumerate() {
-        for (x, char) in line.trim().chars().enumerate() {
+    let mut robotpos = None;
+    for (y, line) in parse::lines(map_section).enumerate() {
+        for (x, (i, char)) in line.char_indices().enumerate() {
             let co = Co::new(x as i64, y as i64);
             match char {
                 '#' => {
@@ -33,22 +42,36 @@ fn parse(input: &str) -> (Map, Co, Vec<Co>) {
                     map.insert(co, Object::Box);
                 }
                 '@' => {
+                    if robotpos.is_some() {
+                        let token = &line[i..i + 1];
+                        return Err(Error::parse(15, input, token, "only one robot '@'"));
+                    }
                     map.insert(co, Object::Robot);
-                    robotpos = co;
+                    robotpos = Some(co);
+                }
+                _ => {
+                    let token = &line[i..i + char.len_utf8()];
+                    return Err(Error::parse(15, input, token, "one of '#.O@'"));
                 }
-                _ => panic!("unexpected char"),
             }
         }
     }
 
-    let moves: Vec<Co> = move_section
-        .trim()
-        .chars()
-        .filter_map(move_to_dir)
-        .rev()
-        .collect();
+    let mut moves = vec![];
+    for (i, char) in move_section.char_indices() {
+        if char.is_whitespace() {
+            continue;
+        }
+        let Some(dir) = Dir4::from_arrow(char) else {
+            let token = &move_section[i..i + char.len_utf8()];
+            return Err(Error::parse(15, input, token, "one of '<>^v'"));
+        };
+        moves.push(dir);
+    }
 
-    (map, robotpos, moves)
+    let robotpos = robotpos.ok_or_else(|| Error::truncated(15, "a robot '@'"))?;
+
+    Ok((map, robotpos, moves))
 }
 
 fn expand_map(map: Map, robot: Co) -> (Map, Co) {
@@ -80,194 +103,245 @@ fn expand_map(map: Map, robot: Co) -> (Map, Co) {
     (new_map, new_robot)
 }
 
-fn move_to_dir(c: char) -> Option<Co> {
-    match c {
-        '<' => Some(Vector2::new(-1, 0)),
-        '>' => Some(Vector2::new(1, 0)),
-        '^' => Some(Vector2::new(0, -1)),
-        'v' => Some(Vector2::new(0, 1)),
-        '\n' => None,
-        _ => panic!("bad char"),
-    }
-}
+/// Everything that moves if the robot at `pos` pushes towards `dir`, the
+/// robot included, or `None` if a wall is in the way.
+fn pushed(map: &Map, pos: Co, dir: Co) -> Option<Vec<Co>> {
+    let x1 = Co::new(1, 0);
+    let mut seen = HashSet::from([pos]);
+    let mut out = vec![pos];
+    let mut rays = VecDeque::from([pos]);
 
-fn unoccupied(map: &Map, pos: &Co, dir: &Co) -> Option<(Co, Vec<Co>)> {
-    let mut mult = 1;
-    let mut intervening = vec![*pos];
-    loop {
-        let offset = dir * mult;
-        let new_co = pos + offset;
+    while let Some(pos) = rays.pop_front() {
+        let new_co = pos + dir;
+        let mut push = |co: Co| {
+            if seen.insert(co) {
+                out.push(co);
+                rays.push_back(co);
+            }
+        };
 
-        let o = map.get(&new_co);
-        match o {
-            Some(Object::Robot) | Some(Object::Box) => {
-                intervening.push(new_co);
-                mult += 1;
-                continue;
+        match map.get(&new_co) {
+            Some(Object::Robot) | Some(Object::Box) => push(new_co),
+            // a wide box drags its other half along when pushed vertically
+            Some(Object::BoxLeft) => {
+                push(new_co);
+                if dir.y != 0 {
+                    push(new_co + x1);
+                }
             }
-            Some(&Object::Wall) => return None,
-            None => return Some((new_co, intervening)),
-            _ => panic!(),
+            Some(Object::BoxRight) => {
+                push(new_co);
+                if dir.y != 0 {
+                    push(new_co - x1);
+                }
+            }
+            Some(Object::Wall) => return None,
+            None => {}
         }
     }
+
+    Some(out)
+}
+
+/// Move every object at `from` by `dir`, all at once.
+fn shift(map: &mut Map, from: &[Co], dir: Co) {
+    let objects: Vec<_> = from
+        .iter()
+        .map(|co| (*co, map.remove(co).expect("cell should not be empty")))
+        .collect();
+    for (co, o) in objects {
+        map.insert(co + dir, o);
+    }
 }
 
 fn box_score(co: &Co) -> i64 {
     co.y * 100 + co.x
 }
 
-fn map_score(map: &Map) -> i64 {
-    map.iter()
-        .map(|(k, v)| match v {
-            Object::Box => box_score(k),
-            _ => 0,
-        })
-        .sum()
+/// One move the robot has made.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Move {
+    pub dir: Dir4,
+    /// Where everything that moved was beforehand, robot first; empty if
+    /// the robot was blocked.
+    pub pushed: Vec<Point>,
 }
 
-fn make_move(state: (Map, Co, Vec<Co>)) -> (Map, Co, Vec<Co>) {
-    let (mut map, mut robot, mut moves) = state;
-    let move_len = moves.len();
-    let Some(next_move) = moves.pop() else {
-        println!("no moves remaining");
-        return (map, robot, vec![]);
-    };
+/// The warehouse part way through the robot's moves, which can be run
+/// forwards a step at a time and wound back again.
+#[derive(Clone, Debug)]
+pub struct Warehouse {
+    map: Map,
+    robot: Co,
+    moves: Vec<Dir4>,
+    log: Vec<Move>,
+}
 
-    assert_eq!(map.get(&robot), Some(&Object::Robot));
+impl Warehouse {
+    pub fn new(input: &str) -> Result<Warehouse, Error> {
+        let (map, robot, moves) = parse(input)?;
+        Ok(Warehouse {
+            map,
+            robot,
+            moves,
+            log: vec![],
+        })
+    }
 
-    let Some((_unocc, mut intervening)) = unoccupied(&map, &robot, &next_move) else {
-        // if we can't make the move, we jump to the next move
-        assert_eq!(moves.len(), move_len - 1);
-        return (map, robot, moves);
-    };
+    /// The part 2 warehouse, with everything but the robot twice as wide.
+    pub fn wide(input: &str) -> Result<Warehouse, Error> {
+        let (map, robot, moves) = parse(input)?;
+        let (map, robot) = expand_map(map, robot);
+        Ok(Warehouse {
+            map,
+            robot,
+            moves,
+            log: vec![],
+        })
+    }
 
-    assert_eq!(_unocc, intervening.last().unwrap() + next_move);
+    /// Make the next move, or return `None` if there are none left.
+    pub fn step(&mut self) -> Option<&Move> {
+        let dir = *self.moves.get(self.log.len())?;
+        let d: Co = dir.delta().into();
 
-    // we have to go from the end to not overwrite anything
-    while let Some(i) = intervening.pop() {
-        let nxt_i = i + next_move;
-        let o = *map.get(&i).expect("cell should not be empty");
-        if o == Object::Robot {
-            robot = nxt_i;
-        }
-        map.insert(nxt_i, o);
-        map.remove(&i);
+        let pushed = match pushed(&self.map, self.robot, d) {
+            Some(from) => {
+                shift(&mut self.map, &from, d);
+                self.robot += d;
+                from.into_iter().map(Point::from).collect()
+            }
+            None => vec![],
+        };
+
+        self.log.push(Move { dir, pushed });
+        self.log.last()
     }
 
-    (map, robot, moves)
-}
+    /// Step until `stop` holds after a move, returning whether it did
+    /// before the moves ran out.
+    pub fn run_until(&mut self, mut stop: impl FnMut(&Warehouse) -> bool) -> bool {
+        while self.step().is_some() {
+            if stop(self) {
+                return true;
+            }
+        }
+        false
+    }
 
-fn map_score_2(map: &Map) -> i64 {
-    map.iter()
-        .map(|(k, v)| match v {
-            Object::BoxLeft => box_score(k),
-            _ => 0,
-        })
-        .sum()
-}
+    /// Make every remaining move.
+    pub fn run(&mut self) {
+        self.run_until(|_| false);
+    }
 
-fn unoccupied_2(map: &Map, pos: &Co, dir: &Co) -> Option<(Co, Vec<Co>)> {
-    let mut intervening = vec![*pos];
-    let mut rays = VecDeque::new();
-    rays.push_back(*pos);
-    let x1 = Co::new(1, 0);
-    loop {
-        let Some(pos) = rays.pop_front() else {
-            break;
-        };
-        intervening.push(pos);
-        let new_co = pos + dir;
+    /// Take back the last move, returning it.
+    pub fn undo(&mut self) -> Option<Move> {
+        let step = self.log.pop()?;
+        let d: Co = step.dir.delta().into();
 
-        let o = map.get(&new_co);
-        match o {
-            Some(Object::Robot) | Some(Object::Box) => {
-                panic!("we shouldn't be here!");
-            }
-            Some(Object::BoxLeft) => {
-                rays.push_back(new_co);
-                if dir.y != 0 {
-                    rays.push_back(new_co + x1);
-                }
-            }
-            Some(Object::BoxRight) => {
-                rays.push_back(new_co);
-                if dir.y != 0 {
-                    rays.push_back(new_co - x1);
-                }
-            }
-            // if we ever hit a wall, we can't move
-            Some(Object::Wall) => return None,
-            None => {}
+        let moved: Vec<Co> = step.pushed.iter().map(|&p| Co::from(p) + d).collect();
+        if !moved.is_empty() {
+            shift(&mut self.map, &moved, -d);
+            self.robot -= d;
         }
+
+        Some(step)
     }
 
-    Some((*pos, intervening))
-}
+    pub fn robot(&self) -> Point {
+        self.robot.into()
+    }
 
-fn make_move_2(state: (Map, Co, Vec<Co>)) -> (Map, Co, Vec<Co>) {
-    let (mut map, mut robot, mut moves) = state;
-    let move_len = moves.len();
-    let Some(next_move) = moves.pop() else {
-        println!("no moves remaining");
-        return (map, robot, vec![]);
-    };
+    /// Every box, by its left edge, in reading order.
+    pub fn boxes(&self) -> Vec<Point> {
+        let mut out: Vec<Point> = self
+            .map
+            .iter()
+            .filter(|(_, o)| matches!(o, Object::Box | Object::BoxLeft))
+            .map(|(co, _)| (*co).into())
+            .collect();
+        out.sort_by_key(|p| (p.y, p.x));
+        out
+    }
 
-    assert_eq!(map.get(&robot), Some(&Object::Robot));
+    /// The sum of the boxes' GPS coordinates.
+    pub fn gps(&self) -> i64 {
+        self.map
+            .iter()
+            .filter(|(_, o)| matches!(o, Object::Box | Object::BoxLeft))
+            .map(|(co, _)| box_score(co))
+            .sum()
+    }
 
-    // unoccupied needs to branch now, and check for collisions
-    let Some((_unocc, mut intervening)) = unoccupied_2(&map, &robot, &next_move) else {
-        // if we can't make the move, we jump to the next move
-        assert_eq!(moves.len(), move_len - 1);
-        return (map, robot, moves);
-    };
+    /// The moves made so far, in order.
+    pub fn log(&self) -> &[Move] {
+        &self.log
+    }
 
-    // assert_eq!(_unocc, intervening.last().unwrap() + next_move);
+    /// The moves still to come.
+    pub fn remaining(&self) -> &[Dir4] {
+        &self.moves[self.log.len()..]
+    }
 
-    // we have to go from the end to not overwrite anything
-    loop {
-        let Some(i) = intervening.pop() else {
-            break;
-        };
-        let nxt_i = i + next_move;
-        let o = map.get(&i);
-        if o == Some(&Object::Robot) {
-            robot = nxt_i;
-        }
-        if let Some(&o) = o {
-            map.insert(nxt_i, o);
-            map.remove(&i);
-        } else {
-            // println!("found blank?");
-        }
+    pub fn render(&self) -> IntMap<Style> {
+        picture(&self.map)
     }
+}
+
+/// Draw the warehouse, sized to fit its walls.
+fn picture(map: &Map) -> IntMap<Style> {
+    let width = map.keys().map(|co| co.x + 1).max().unwrap_or(0);
+    let height = map.keys().map(|co| co.y + 1).max().unwrap_or(0);
+
+    let cells = map.iter().map(|(co, o)| {
+        let style = match o {
+            Object::Robot => Style::plain('@').fg(WHITE).bg(RED),
+            Object::Box => Style::plain('O').fg(YELLOW),
+            Object::BoxLeft => Style::plain('[').fg(YELLOW),
+            Object::BoxRight => Style::plain(']').fg(YELLOW),
+            Object::Wall => Style::plain('#').fg(GREY),
+        };
+        ((co.x as isize, co.y as isize), style)
+    });
 
-    (map, robot, moves)
+    render::sparse(width as usize, height as usize, Style::default(), cells)
 }
 
 pub struct Day15 {}
 
 impl Day<i64> for Day15 {
+    type Params = ();
+
     fn part1(input: &str) -> i64 {
-        let mut s = parse(input);
+        Self::try_part1(input).unwrap_or_else(|e| panic!("{e}"))
+    }
 
-        while !&s.2.is_empty() {
-            // println!("{:?} / {:?}", s.1, s.2);
-            s = make_move(s);
-        }
+    fn part2(input: &str) -> i64 {
+        Self::try_part2(input).unwrap_or_else(|e| panic!("{e}"))
+    }
 
-        map_score(&s.0)
+    fn validate(input: &str) -> Option<Result<(), Error>> {
+        Some(parse(input).map(|_| ()))
     }
 
-    fn part2(input: &str) -> i64 {
-        let mut s = parse(input);
-        (s.0, s.1) = expand_map(s.0, s.1);
+    fn try_part1(input: &str) -> Result<i64, Error> {
+        let mut w = Warehouse::new(input)?;
+        w.run();
+        Ok(w.gps())
+    }
 
-        while !&s.2.is_empty() {
-            // println!("{:?} / {:?}", s.1, s.2);
-            s = make_move_2(s);
-        }
+    fn try_part2(input: &str) -> Result<i64, Error> {
+        let mut w = Warehouse::wide(input)?;
+        w.run();
+        Ok(w.gps())
+    }
 
-        map_score_2(&s.0)
+    /// The wide warehouse once the robot has made every move.
+    fn render(input: &str, _params: &()) -> Option<Result<IntMap<Style>, Error>> {
+        Some(Warehouse::wide(input).map(|mut w| {
+            w.run();
+            w.render()
+        }))
     }
 }
 
@@ -322,6 +396,128 @@ v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^
         assert_eq!(2028, Day15::part1(TEST_INPUT_SMALL));
     }
 
+    #[test]
+    fn crlf() {
+        let input = TEST_INPUT.replace('\n', "\r\n");
+        assert_eq!(10092, Day15::part1(&input));
+        assert_eq!(9021, Day15::part2(&input));
+    }
+
+    #[test]
+    fn stepping() {
+        let mut w = Warehouse::new(TEST_INPUT_SMALL).unwrap();
+        let start = w.render().ascii();
+        assert_eq!(Point::new(2, 2), w.robot());
+        assert_eq!(15, w.remaining().len());
+
+        assert_eq!(Some(&vec![]), w.step().map(|m| &m.pushed));
+        for _ in 0..4 {
+            w.step();
+        }
+        assert_eq!(Point::new(4, 1), w.robot());
+        assert_eq!(
+            Move {
+                dir: Dir4::East,
+                pushed: vec![Point::new(3, 1), Point::new(4, 1), Point::new(5, 1)],
+            },
+            w.log()[4]
+        );
+        assert!(w.step().unwrap().pushed.is_empty());
+        assert_eq!(Point::new(5, 1), w.boxes()[0]);
+
+        assert_eq!(Some(Dir4::East), w.undo().map(|m| m.dir));
+        assert_eq!(Some(Dir4::East), w.undo().map(|m| m.dir));
+        assert_eq!(Point::new(3, 1), w.robot());
+        assert_eq!(4, w.log().len());
+
+        w.run();
+        assert_eq!(2028, w.gps());
+        assert!(w.step().is_none());
+        while w.undo().is_some() {}
+        assert_eq!(start, w.render().ascii());
+
+        assert!(w.run_until(|w| w.robot() == Point::new(4, 1)));
+        assert_eq!(5, w.log().len());
+        assert!(!w.run_until(|w| w.robot() == Point::new(0, 0)));
+    }
+
+    #[test]
+    fn wide_undo() {
+        let mut w = Warehouse::wide(TEST_INPUT).unwrap();
+        let start = w.render().ascii();
+        w.run();
+        assert_eq!(9021, w.gps());
+        assert!(w.log().iter().any(|m| m.pushed.len() > 4));
+        while w.undo().is_some() {}
+        assert_eq!(start, w.render().ascii());
+    }
+
+    #[test]
+    fn render_t() {
+        let m = Day15::render(TEST_INPUT, &()).unwrap().unwrap();
+        assert_eq!(
+            "####################
+##[].......[].[][]##
+##[]...........[].##
+##[]........[][][]##
+##[]......[]....[]##
+##..##......[]....##
+##..[]............##
+##..@......[].[][]##
+##......[][]..[]..##
+####################
+",
+            m.ascii()
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        let truncated = &TEST_INPUT_SMALL[..TEST_INPUT_SMALL.find("\n\n").unwrap()];
+        assert!(matches!(
+            parse(truncated),
+            Err(Error::Truncated { day: 15, .. })
+        ));
+
+        let bad_tile = TEST_INPUT_SMALL.replacen("#.#", "#?#", 1);
+        assert!(matches!(
+            parse(&bad_tile),
+            Err(Error::Parse {
+                line: 6,
+                column: 2,
+                ..
+            })
+        ));
+
+        let bad_move = TEST_INPUT_SMALL.replace("<^^>", "<^x>");
+        assert_eq!(
+            "day 15, line 11, column 3: expected one of '<>^v', found \"x\"",
+            parse(&bad_move).unwrap_err().to_string()
+        );
+
+        let no_robot = TEST_INPUT_SMALL.replace('@', ".");
+        assert_eq!(
+            Some(Error::truncated(15, "a robot '@'")),
+            parse(&no_robot).err()
+        );
+
+        let two_robots = TEST_INPUT_SMALL.replacen("#.#", "#@#", 1);
+        assert_eq!(
+            "day 15, line 6, column 2: expected only one robot '@', found \"@\"",
+            parse(&two_robots).unwrap_err().to_string()
+        );
+
+        let trailing = format!("{TEST_INPUT_SMALL}\n\n<<>>\n");
+        assert!(matches!(
+            parse(&trailing),
+            Err(Error::Parse {
+                token,
+                expected: "the end of the input",
+                ..
+            }) if token == "<<>>"
+        ));
+    }
+
     #[test]
     fn part2_t() {
         assert_eq!(9021, Day15::part2(TEST_INPUT));
@@ -1,6 +1,12 @@
-use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::collections::HashSet;
 
-use crate::{intmap::IntMap, Day};
+use crate::{
+    astar, dijkstra,
+    intmap::IntMap,
+    parse,
+    render::{Style, GREEN, GREY, RED},
+    Day, Dir4, Error,
+};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
 enum Object {
@@ -11,168 +17,167 @@ enum Object {
     Empty,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-enum Dir {
-    North,
-    South,
-    East,
-    West,
+type Co = (isize, isize);
+
+fn parse(input: &str) -> Result<(IntMap<Object>, Co, Co), Error> {
+    let m = parse::grid(16, input, input, "one of '#.SE'", |c| match c {
+        '#' => Some(Object::Wall),
+        'S' => Some(Object::Start),
+        'E' => Some(Object::End),
+        '.' => Some(Object::Empty),
+        _ => None,
+    })?;
+
+    let start = m
+        .find(&Object::Start)
+        .ok_or(Error::truncated(16, "a start tile 'S'"))?;
+    let end = m
+        .find(&Object::End)
+        .ok_or(Error::truncated(16, "an end tile 'E'"))?;
+
+    Ok((m, start, end))
 }
 
-fn parse(input: &str) -> (IntMap<Object>, (isize, isize), (isize, isize)) {
-    let m = IntMap::from_raw(input, |c| match c {
-        '#' => Object::Wall,
-        'S' => Object::Start,
-        'E' => Object::End,
-        '.' => Object::Empty,
-        _ => panic!("bad char"),
-    });
-
-    let mut start = (-1, -1);
-    let mut end = (-1, -1);
-    for (idx, n) in m.cells.iter().enumerate() {
-        if n == &Object::Start {
-            start = m.idx_to_pt(idx as isize);
-        }
-        if n == &Object::End {
-            end = m.idx_to_pt(idx as isize);
-        }
-    }
+type State = (Co, Dir4);
 
-    (m, start, end)
+fn open(m: &IntMap<Object>, co: Co) -> bool {
+    m.get(co).is_some_and(|o| *o != Object::Wall)
 }
 
-fn adjacent_dirs(d: Dir) -> [Dir; 2] {
-    match d {
-        Dir::East | Dir::West => [Dir::North, Dir::South],
-        Dir::North | Dir::South => [Dir::East, Dir::West],
-    }
-}
-
-fn delta(d: Dir) -> (isize, isize) {
-    match d {
-        Dir::East => (1, 0),
-        Dir::West => (-1, 0),
-        Dir::North => (0, -1),
-        Dir::South => (0, 1),
-    }
+/// Everything the reindeer can do from `state`, with the cost of doing it.
+fn moves(m: &IntMap<Object>, (pos, dir): State) -> impl Iterator<Item = (State, u64)> {
+    let d = dir.delta();
+    let fwd = (pos.0 + d.x, pos.1 + d.y);
+    let step = open(m, fwd).then_some(((fwd, dir), 1));
+
+    [
+        ((pos, dir.clockwise()), 1000),
+        ((pos, dir.anticlockwise()), 1000),
+    ]
+    .into_iter()
+    .chain(step)
 }
 
-#[derive(Clone, Eq, PartialEq)]
-struct QueueState(i32, (isize, isize), Dir, HashSet<(u8, u8)>);
-
-impl Ord for QueueState {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        other.0.cmp(&self.0)
-    }
+fn lowest_score(m: &IntMap<Object>, start: Co, end: Co) -> Option<u64> {
+    // manhattan should be an appropriate minimum cost, plus a turn if we
+    // aren't lined up yet. A single step can line us up and drop the
+    // estimate by 1001, so it's admissible but not consistent; astar only
+    // needs the former
+    let estimate = |&(pos, _): &State| {
+        let turn = if pos.0 != end.0 && pos.1 != end.1 {
+            1000
+        } else {
+            0
+        };
+        (pos.0.abs_diff(end.0) + pos.1.abs_diff(end.1)) as u64 + turn
+    };
+
+    astar(
+        (start, Dir4::East),
+        |&s| moves(m, s),
+        estimate,
+        |s| s.0 == end,
+    )
+    .map(|(cost, _)| cost)
 }
 
-impl PartialOrd for QueueState {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
-    }
+/// The lowest-scoring routes through a maze.
+#[derive(Clone, Debug)]
+pub struct Seats {
+    pub score: u64,
+    /// Every tile on at least one of the routes.
+    pub tiles: HashSet<(isize, isize)>,
+    /// How many routes there are, counting each different sequence of
+    /// moves and turns separately.
+    pub paths: u64,
 }
 
-fn manhattan(src: &(isize, isize), dest: &(isize, isize)) -> isize {
-    (dest.0 - src.0).abs() + (dest.1 - src.1).abs()
+fn best_seats(m: &IntMap<Object>, start: Co, end: Co) -> Option<Seats> {
+    let from_start = dijkstra([(start, Dir4::East)], |&s| moves(m, s));
+
+    let ends: Vec<State> = Dir4::ALL.iter().map(|&d| (end, d)).collect();
+    let score = ends.iter().filter_map(|s| from_start.distance(s)).min()?;
+    let ends: Vec<&State> = ends
+        .iter()
+        .filter(|s| from_start.distance(s) == Some(score))
+        .collect();
+
+    Some(Seats {
+        score,
+        tiles: from_start
+            .ancestors(ends.iter().copied())
+            .into_iter()
+            .map(|(pos, _)| pos)
+            .collect(),
+        paths: from_start.count_paths(ends.iter().copied()),
+    })
 }
 
-fn dijkstra(
-    m: &IntMap<Object>,
-    start: (isize, isize),
-    start_dir: Dir,
-    end: (isize, isize),
-    first_only: bool,
-) -> Option<(i64, HashSet<(u8, u8)>)> {
-    let mut seen = HashMap::<((isize, isize), Dir), i32>::new();
-    let mut q = BinaryHeap::<QueueState>::with_capacity(4096);
-    q.push(QueueState(0, start, start_dir, HashSet::new()));
-
-    let mut best_cost = i32::MAX;
-    let mut bests = HashMap::<i32, HashSet<(u8, u8)>>::new();
-
-    loop {
-        let Some(QueueState(cost, pos, dir, route)) = q.pop() else {
-            break;
-        };
-
-        if cost > best_cost {
-            break;
-        }
-
-        // manhattan should be an appropriate minimum cost
-        let mut cost_est = manhattan(&pos, &end) as i32;
-        if pos.0 != end.0 && pos.1 != end.1 {
-            cost_est += 1000;
-        }
-        if cost_est > best_cost {
-            continue;
-        }
-
-        if let Some(&val) = seen.get(&(pos, dir)) {
-            if cost > val || first_only {
-                continue;
-            }
-        }
+pub struct Day16 {}
 
-        // record it as seen, with updated lowest cost as necessary
-        seen.entry((pos, dir))
-            .and_modify(|v| *v = (*v).min(cost))
-            .or_insert(cost);
-
-        let mut route = route;
-        route.insert((pos.0 as u8, pos.1 as u8));
-
-        if pos == end {
-            // println!("found a route {} / {}", cost, new_route.len());
-            best_cost = best_cost.min(cost);
-            let r = bests.entry(cost).or_default();
-            r.extend(route);
-
-            if first_only {
-                break;
-            } else {
-                continue;
-            }
-        }
+impl Day16 {
+    /// All of the lowest-scoring routes from the start to the end.
+    pub fn seats(input: &str) -> Result<Seats, Error> {
+        let (m, s, e) = parse(input)?;
+        best_seats(&m, s, e).ok_or_else(|| Error::no_answer(16, "no route to the end"))
+    }
+}
 
-        for nextd in adjacent_dirs(dir) {
-            q.push(QueueState(cost + 1000, pos, nextd, route.clone()));
-        }
+impl Day<i64> for Day16 {
+    type Params = ();
 
-        let fwd = {
-            let dxy = delta(dir);
-            (pos.0 + dxy.0, pos.1 + dxy.1)
-        };
-        if let Some(v) = m.get(fwd) {
-            if *v != Object::Wall {
-                q.push(QueueState(cost + 1, fwd, dir, route));
-            }
-        }
+    fn part1(input: &str) -> i64 {
+        Self::try_part1(input).unwrap_or_else(|e| panic!("{e}"))
     }
 
-    let best_set = bests.remove(&best_cost).unwrap();
-
-    Some((best_cost as i64, best_set))
-}
+    fn part2(input: &str) -> i64 {
+        Self::try_part2(input).unwrap_or_else(|e| panic!("{e}"))
+    }
 
-pub struct Day16 {}
+    fn validate(input: &str) -> Option<Result<(), Error>> {
+        Some(parse(input).map(|_| ()))
+    }
 
-impl Day<i64> for Day16 {
-    fn part1(input: &str) -> i64 {
-        let (m, s, e) = parse(input);
+    fn try_part1(input: &str) -> Result<i64, Error> {
+        let (m, s, e) = parse(input)?;
 
-        let (cost, _visited) = dijkstra(&m, s, Dir::East, e, true).unwrap();
+        let cost =
+            lowest_score(&m, s, e).ok_or_else(|| Error::no_answer(16, "no route to the end"))?;
 
-        cost
+        Ok(cost as i64)
     }
 
-    fn part2(input: &str) -> i64 {
-        let (m, s, e) = parse(input);
+    fn try_part2(input: &str) -> Result<i64, Error> {
+        Ok(Day16::seats(input)?.tiles.len() as i64)
+    }
 
-        let (_, visited) = dijkstra(&m, s, Dir::East, e, false).unwrap();
+    /// The maze with every tile on a best route marked `O`.
+    fn render(input: &str, _params: &()) -> Option<Result<IntMap<Style>, Error>> {
+        let draw = || {
+            let (m, s, e) = parse(input)?;
+            let seats =
+                best_seats(&m, s, e).ok_or_else(|| Error::no_answer(16, "no route to the end"))?;
+
+            let mut pic = m.map(|o| match o {
+                Object::Wall => Style::plain('#').fg(GREY),
+                Object::Empty => Style::default(),
+                Object::Start => Style::plain('S'),
+                Object::End => Style::plain('E'),
+            });
+            pic.overlay(
+                seats
+                    .tiles
+                    .iter()
+                    .map(|&co| (co, Style::plain('O').fg(GREEN))),
+            );
+            pic.overlay([
+                (s, Style::plain('S').fg(RED)),
+                (e, Style::plain('E').fg(RED)),
+            ]);
+            Ok(pic)
+        };
 
-        visited.len() as i64
+        Some(draw())
     }
 }
 
@@ -223,6 +228,46 @@ mod test {
         assert_eq!(7036, Day16::part1(TEST_INPUT));
     }
 
+    #[test]
+    fn parse_errors() {
+        let bad = TEST_INPUT.replace("#S..#", "#S.o#");
+        assert!(matches!(
+            parse(&bad),
+            Err(Error::Parse {
+                line: 15,
+                column: 8,
+                ..
+            })
+        ));
+
+        let no_end = TEST_INPUT.replace('E', ".");
+        assert_eq!(
+            Err(Error::truncated(16, "an end tile 'E'")),
+            parse(&no_end).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn walled_off() {
+        let maze = "
+            #####
+            #S#E#
+            #####
+        ";
+        assert_eq!(
+            Err(Error::no_answer(16, "no route to the end")),
+            Day16::try_part1(maze)
+        );
+        assert_eq!(
+            Err(Error::no_answer(16, "no route to the end")),
+            Day16::try_part2(maze)
+        );
+        assert!(matches!(
+            Day16::render(maze, &()),
+            Some(Err(Error::NoAnswer { day: 16, .. }))
+        ));
+    }
+
     #[test]
     fn part2_small() {
         assert_eq!(45, Day16::part2(TEST_INPUT));
@@ -233,13 +278,50 @@ mod test {
         assert_eq!(64, Day16::part2(TEST_INPUT_2));
     }
 
+    #[test]
+    fn seats() {
+        let seats = Day16::seats(TEST_INPUT).unwrap();
+        assert_eq!(7036, seats.score);
+        assert_eq!(45, seats.tiles.len());
+        assert_eq!(3, seats.paths);
+
+        let pic = Day16::render(TEST_INPUT, &()).unwrap().unwrap();
+        let marked = pic.cells.iter().filter(|s| s.glyph == 'O').count();
+        assert_eq!(45, marked + 2);
+        assert!(pic
+            .ascii()
+            .starts_with("###############\n#.......#....E#\n"));
+
+        let seats = Day16::seats(TEST_INPUT_2).unwrap();
+        assert_eq!(11048, seats.score);
+        assert_eq!(2, seats.paths);
+    }
+
+    #[test]
+    fn wide_maze() {
+        // wider than a u8 coordinate can reach
+        let row = |c: char| format!("#{}#\n", c.to_string().repeat(300));
+        let input = format!(
+            "{}{}{}",
+            "#".repeat(302) + "\n",
+            row('.').replacen('.', "S", 1).replacen(".#", "E#", 1),
+            "#".repeat(302)
+        );
+
+        let (m, s, e) = parse(&input).unwrap();
+        let seats = best_seats(&m, s, e).unwrap();
+        assert_eq!(299, seats.score);
+        assert_eq!(300, seats.tiles.len());
+        assert_eq!(1, seats.paths);
+    }
+
     #[test]
     #[ignore]
     fn bench_clone_hashset() {
         let mut h = HashSet::new();
-        h.insert(((1, 2), Dir::East));
-        h.insert(((2, 3), Dir::West));
-        h.insert(((2, 3), Dir::West));
+        h.insert(((1, 2), Dir4::East));
+        h.insert(((2, 3), Dir4::West));
+        h.insert(((2, 3), Dir4::West));
 
         for _i in 0..10_000_000 {
             let h2 = h.clone();
@@ -254,9 +336,9 @@ mod test {
     #[ignore]
     fn bench_clone_vec() {
         let mut h = Vec::new();
-        h.push(((1, 2), Dir::East));
-        h.push(((2, 3), Dir::West));
-        h.push(((2, 3), Dir::West));
+        h.push(((1, 2), Dir4::East));
+        h.push(((2, 3), Dir4::West));
+        h.push(((2, 3), Dir4::West));
 
         for _i in 0..10_000_000 {
             let h2 = h.clone();
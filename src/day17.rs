@@ -1,201 +1,344 @@
-use crate::Day;
+use std::fmt::Display;
 
-#[derive(Clone, Debug)]
-struct Machine {
-    a: u64,
-    b: u64,
-    c: u64,
+use crate::{params, parse, Day, Error};
 
-    ip: usize,
-    program: Vec<u64>,
+mod asm;
+mod compile;
+mod debug;
+mod disasm;
+mod quine;
+
+pub use compile::Compiled;
+pub use debug::{Breakpoint, Debugger, Stop, TraceEntry};
+pub use quine::{QuineError, Shape};
+
+/// The three-bit computer: three registers, an instruction pointer and a
+/// program of 3-bit words.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Machine {
+    pub a: u64,
+    pub b: u64,
+    pub c: u64,
+
+    pub ip: usize,
+    pub program: Vec<u64>,
+}
+
+impl Machine {
+    pub fn new(input: &str) -> Result<Machine, Error> {
+        parse(input)
+    }
+
+    /// A machine with all its registers at 0, ready to run `program`.
+    pub fn from_program(program: &[u64]) -> Machine {
+        Machine {
+            a: 0,
+            b: 0,
+            c: 0,
+            ip: 0,
+            program: program.to_vec(),
+        }
+    }
 }
 
-fn parse(input: &str) -> Machine {
+fn parse(input: &str) -> Result<Machine, Error> {
     let mut a = 0;
     let mut b = 0;
     let mut c = 0;
     let mut p = vec![];
 
-    for line in input.lines() {
-        let line = line.trim();
-        if line.is_empty() {
-            continue;
-        }
+    let number = |s: &str| {
+        s.trim()
+            .parse::<u64>()
+            .map_err(|_| Error::parse(17, input, s.trim(), "a number"))
+    };
 
-        let (left, right) = line.split_once(": ").unwrap();
+    for line in parse::lines(input) {
+        let (left, right) = line
+            .split_once(": ")
+            .ok_or_else(|| Error::parse(17, input, line, "'<name>: <value>'"))?;
 
         match left {
-            "Register A" => {
-                a = right.parse().unwrap();
-            }
-            "Register B" => {
-                b = right.parse().unwrap();
-            }
-            "Register C" => {
-                c = right.parse().unwrap();
-            }
+            "Register A" => a = number(right)?,
+            "Register B" => b = number(right)?,
+            "Register C" => c = number(right)?,
             "Program" => {
-                p = right.split(",").map(|v| v.parse().unwrap()).collect();
-            }
-            _ => {
-                println!("unhandled line {}", line);
+                for word in right.split(',') {
+                    let w = number(word)?;
+                    if w > 7 {
+                        return Err(Error::parse(17, input, word.trim(), "a 3-bit number"));
+                    }
+                    p.push(w);
+                }
             }
+            _ => return Err(Error::parse(17, input, left, "a register or the program")),
         }
     }
 
-    Machine {
+    Ok(Machine {
         a,
         b,
         c,
         program: p,
         ip: 0,
-    }
+    })
 }
 
-#[inline(always)]
-fn combo(m: &Machine, operand: u64) -> u64 {
-    match operand {
-        0 => 0,
-        1 => 1,
-        2 => 2,
-        3 => 3,
-        4 => m.a,
-        5 => m.b,
-        6 => m.c,
-        7 => panic!("reserved"),
-        _ => panic!("unexpected operand {operand}"),
-    }
+/// The eight instructions, in opcode order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Op {
+    Adv,
+    Bxl,
+    Bst,
+    Jnz,
+    Bxc,
+    Out,
+    Bdv,
+    Cdv,
 }
 
-fn run(m: Machine) -> Vec<u64> {
-    let mut m = m;
-    let mut outputs = Vec::<u64>::new();
+impl Op {
+    pub const ALL: [Op; 8] = [
+        Op::Adv,
+        Op::Bxl,
+        Op::Bst,
+        Op::Jnz,
+        Op::Bxc,
+        Op::Out,
+        Op::Bdv,
+        Op::Cdv,
+    ];
 
-    loop {
-        match m.program[m.ip] {
-            0 => {
-                // adv
-                let num = m.a;
-                let denom = (2_u64).pow(combo(&m, m.program[m.ip + 1]) as u32);
-                m.a = num / denom;
-                m.ip += 2;
-            }
-            1 => {
-                // bxl
-                let lit = m.program[m.ip + 1];
-                m.b ^= lit;
-                m.ip += 2;
-            }
-            2 => {
-                // bst
-                let op = combo(&m, m.program[m.ip + 1]);
-                m.b = op & 0b111;
-                m.ip += 2;
-            }
-            3 => {
-                // jnz
-                if m.a != 0 {
-                    m.ip = m.program[m.ip + 1] as usize;
-                } else {
-                    m.ip += 2;
-                }
-            }
-            4 => {
-                // bxc
-                m.b ^= m.c;
-                m.ip += 2;
-            }
-            5 => {
-                // out
-                outputs.push(combo(&m, m.program[m.ip + 1]) & 0b111);
-                m.ip += 2;
-            }
-            6 => {
-                // bdv
-                let num = m.a;
-                let denom = (2_u64).pow(combo(&m, m.program[m.ip + 1]) as u32);
-                m.b = num / denom;
-                m.ip += 2;
-            }
-            7 => {
-                // cdv
-                let num = m.a;
-                let denom = (2_u64).pow(combo(&m, m.program[m.ip + 1]) as u32);
-                m.c = num / denom;
-                m.ip += 2;
-            }
-            _ => {
-                let opcode = m.program[m.ip];
-                panic!("unhandled opcode {opcode}");
-            }
-        }
+    pub fn from_code(code: u64) -> Option<Op> {
+        Op::ALL.get(usize::try_from(code).ok()?).copied()
+    }
 
-        if m.ip >= m.program.len() {
-            break;
+    pub fn code(self) -> u64 {
+        self as u64
+    }
+
+    pub fn mnemonic(self) -> &'static str {
+        match self {
+            Op::Adv => "adv",
+            Op::Bxl => "bxl",
+            Op::Bst => "bst",
+            Op::Jnz => "jnz",
+            Op::Bxc => "bxc",
+            Op::Out => "out",
+            Op::Bdv => "bdv",
+            Op::Cdv => "cdv",
         }
     }
 
-    outputs
+    /// Whether the operand is a combo operand, rather than a literal.
+    pub fn takes_combo(self) -> bool {
+        matches!(self, Op::Adv | Op::Bst | Op::Out | Op::Bdv | Op::Cdv)
+    }
 }
 
-fn recur(machine: &Machine, target: &[u64], current: u64) -> Option<u64> {
-    for idx in 0..8 {
-        let mut m = machine.clone();
-        let nxt = current * 8 + idx;
+/// An operand as its instruction reads it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Operand {
+    Lit(u64),
+    A,
+    B,
+    C,
+    /// Combo operand 7, which a valid program never uses.
+    Reserved,
+}
 
-        if nxt == 0 {
-            continue;
+impl Operand {
+    pub fn decode(op: Op, raw: u64) -> Operand {
+        if !op.takes_combo() {
+            return Operand::Lit(raw);
         }
+        match raw {
+            0..=3 => Operand::Lit(raw),
+            4 => Operand::A,
+            5 => Operand::B,
+            6 => Operand::C,
+            _ => Operand::Reserved,
+        }
+    }
+}
 
-        m.a = nxt;
-        let res = run(m);
-        // println!("{:?} -> {:?} {:?}", nxt, res, target);
+/// One decoded instruction and where it sits in the program.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Instr {
+    pub addr: usize,
+    pub op: Op,
+    pub operand: Operand,
+}
 
-        // Success condition
-        if res == target {
-            return Some(nxt);
-        }
+/// The program read two words at a time from the start. A trailing odd
+/// word has no operand and is left out.
+pub fn decode(program: &[u64]) -> Result<Vec<Instr>, Fault> {
+    program
+        .chunks_exact(2)
+        .enumerate()
+        .map(|(i, w)| {
+            let ip = i * 2;
+            let op = Op::from_code(w[0]).ok_or(Fault::BadOpcode { ip, code: w[0] })?;
+            Ok(Instr {
+                addr: ip,
+                op,
+                operand: Operand::decode(op, w[1]),
+            })
+        })
+        .collect()
+}
 
-        // overshoot - no point descending
-        if res.len() > target.len() {
-            return None;
+/// Something a program did that the machine can't carry out.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Fault {
+    /// A word that isn't an opcode where an instruction should be.
+    BadOpcode { ip: usize, code: u64 },
+    /// An opcode in the last word, with no operand after it.
+    MissingOperand { ip: usize },
+    /// Combo operand 7.
+    ReservedOperand { ip: usize },
+}
+
+impl Display for Fault {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Fault::BadOpcode { ip, code } => write!(f, "bad opcode {code} at {ip}"),
+            Fault::MissingOperand { ip } => write!(f, "missing operand at {ip}"),
+            Fault::ReservedOperand { ip } => write!(f, "reserved combo operand 7 at {ip}"),
         }
+    }
+}
 
-        // Check res against tail of target
-        let rlen = res.len();
-        let tlen = target.len();
-        let tail = &target[(tlen - rlen)..];
-        assert!(tail.len() <= res.len());
+impl std::error::Error for Fault {}
 
-        // And don't bother recursing if we don't have a matching tail already
-        if tail != res {
-            continue;
-        }
+impl From<Fault> for Error {
+    fn from(f: Fault) -> Error {
+        Error::runtime(17, f.to_string())
+    }
+}
+
+/// What happened on one `step`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    Ran,
+    Output(u64),
+    /// The instruction pointer is past the end, so nothing ran.
+    Halted,
+}
+
+impl Machine {
+    /// The instruction at `ip`, if there's a whole one there.
+    pub fn fetch(&self) -> Result<Option<Instr>, Fault> {
+        let ip = self.ip;
+        let Some(&code) = self.program.get(ip) else {
+            return Ok(None);
+        };
+        let op = Op::from_code(code).ok_or(Fault::BadOpcode { ip, code })?;
+        let &raw = self
+            .program
+            .get(ip + 1)
+            .ok_or(Fault::MissingOperand { ip })?;
 
-        // Otherwise recursing is all we have left!
-        if let Some(v) = recur(machine, target, nxt) {
-            return Some(v);
+        Ok(Some(Instr {
+            addr: ip,
+            op,
+            operand: Operand::decode(op, raw),
+        }))
+    }
+
+    /// Carry out one instruction.
+    pub fn step(&mut self) -> Result<Event, Fault> {
+        let Some(i) = self.fetch()? else {
+            return Ok(Event::Halted);
+        };
+        let value = match i.operand {
+            Operand::Lit(n) => n,
+            Operand::A => self.a,
+            Operand::B => self.b,
+            Operand::C => self.c,
+            Operand::Reserved => return Err(Fault::ReservedOperand { ip: self.ip }),
+        };
+        // dividing by 2^value; anything past 63 bits leaves nothing
+        let shifted = self
+            .a
+            .checked_shr(u32::try_from(value).unwrap_or(u32::MAX))
+            .unwrap_or(0);
+
+        self.ip += 2;
+        match i.op {
+            Op::Adv => self.a = shifted,
+            Op::Bdv => self.b = shifted,
+            Op::Cdv => self.c = shifted,
+            Op::Bxl => self.b ^= value,
+            Op::Bst => self.b = value & 0b111,
+            Op::Jnz => {
+                if self.a != 0 {
+                    self.ip = usize::try_from(value).unwrap_or(usize::MAX);
+                }
+            }
+            Op::Bxc => self.b ^= self.c,
+            Op::Out => return Ok(Event::Output(value & 0b111)),
         }
+
+        Ok(Event::Ran)
     }
+}
 
-    None
+/// Everything the program outputs before it halts.
+/// Everything the program outputs before it halts, giving up once it has
+/// run `max_cycles` instructions.
+fn run(m: Machine, max_cycles: u64) -> Result<Vec<u64>, Error> {
+    let mut d = Debugger::new(m).max_cycles(max_cycles);
+    match d.run()? {
+        Stop::Halted => Ok(d.output().to_vec()),
+        Stop::CycleLimit => Err(Error::runtime(
+            17,
+            format!("still running after {max_cycles} instructions"),
+        )),
+        Stop::Breakpoint(_) => unreachable!("no breakpoints are set"),
+    }
+}
+
+params! {
+    pub struct Day17Params {
+        /// How many instructions part 1 runs before deciding the program
+        /// never halts.
+        pub max_cycles: u64 = 10_000_000,
+    }
 }
 
 pub struct Day17 {}
 
 impl Day<String> for Day17 {
+    type Params = Day17Params;
+
     fn part1(input: &str) -> String {
-        run(parse(input))
+        Self::try_part1(input).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    fn part2(input: &str) -> String {
+        Self::try_part2(input).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    fn validate(input: &str) -> Option<Result<(), Error>> {
+        Some(parse(input).map(|_| ()))
+    }
+
+    fn try_part1(input: &str) -> Result<String, Error> {
+        Self::try_part1_with(input, &Day17Params::default())
+    }
+
+    fn try_part1_with(input: &str, params: &Day17Params) -> Result<String, Error> {
+        Ok(run(parse(input)?, params.max_cycles)?
             .iter()
             .map(|v| v.to_string())
             .collect::<Vec<_>>()
-            .join(",")
+            .join(","))
     }
 
-    fn part2(input: &str) -> String {
-        let m = parse(input);
-        let a_out = recur(&m, &m.program, 0).unwrap();
-        a_out.to_string()
+    fn try_part2(input: &str) -> Result<String, Error> {
+        let m = parse(input)?;
+        Ok(m.find_quine()?.to_string())
     }
 }
 
@@ -213,7 +356,10 @@ mod test {
 
     #[test]
     fn t1() {
-        assert_eq!(vec![4, 6, 3, 5, 6, 3, 5, 2, 1, 0], run(parse(TEST_INPUT)));
+        assert_eq!(
+            vec![4, 6, 3, 5, 6, 3, 5, 2, 1, 0],
+            run(parse(TEST_INPUT).unwrap(), 1000).unwrap()
+        );
     }
 
     const TEST_INPUT_2: &str = "
@@ -228,4 +374,32 @@ mod test {
     fn t2() {
         assert_eq!("117440", Day17::part2(TEST_INPUT_2));
     }
+
+    #[test]
+    fn parse_errors() {
+        let bad = TEST_INPUT.replace("0,1,5", "0,9,5");
+        assert_eq!(
+            "day 17, line 6, column 20: expected a 3-bit number, found \"9\"",
+            Machine::new(&bad).unwrap_err().to_string()
+        );
+        assert!(Machine::new("Register D: 1").is_err());
+        assert!(Machine::new("Register A 1").is_err());
+    }
+
+    #[test]
+    fn faults() {
+        let input = TEST_INPUT.replace("0,1,5,4,3,0", "0,1,5,7");
+        assert_eq!(
+            Err(Error::runtime(17, "reserved combo operand 7 at 2")),
+            Day17::try_part1(&input)
+        );
+
+        // jumps back to itself forever
+        let input = TEST_INPUT.replace("0,1,5,4,3,0", "3,0");
+        let params = Day17Params { max_cycles: 500 };
+        assert_eq!(
+            Err(Error::runtime(17, "still running after 500 instructions")),
+            Day17::try_part1_with(&input, &params)
+        );
+    }
 }
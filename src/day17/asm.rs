@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+
+use super::{Machine, Op};
+use crate::Error;
+
+/// A source line with its comment and any label taken off.
+struct Line<'a> {
+    label: Option<&'a str>,
+    code: &'a str,
+}
+
+fn split_line(line: &str) -> Line<'_> {
+    let code = line.split(';').next().unwrap_or_default();
+    match code.split_once(':') {
+        Some((label, rest)) => Line {
+            label: Some(label.trim()),
+            code: rest.trim(),
+        },
+        None => Line {
+            label: None,
+            code: code.trim(),
+        },
+    }
+}
+
+fn is_register(s: &str) -> bool {
+    matches!(s, "a" | "b" | "c")
+}
+
+fn is_label(s: &str) -> bool {
+    s.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !is_register(s)
+}
+
+impl Machine {
+    /// Assemble a program written the way `disassemble` prints one: an
+    /// instruction per line as `mnemonic operand`, with `name:` labelling
+    /// the next instruction and `;` starting a comment. Combo operands are
+    /// `a`, `b`, `c` or a raw 0-7, `jnz` can go to a label, and `bxc` can
+    /// leave out its ignored operand. The registers all start at 0.
+    pub fn assemble(source: &str) -> Result<Machine, Error> {
+        let mnemonic = |token: &str| {
+            Op::ALL
+                .into_iter()
+                .find(|op| op.mnemonic() == token)
+                .ok_or_else(|| Error::parse(17, source, token, "an instruction"))
+        };
+
+        // first pass: where each label points
+        let mut labels = HashMap::new();
+        let mut addr = 0;
+        for line in source.lines().map(split_line) {
+            if let Some(label) = line.label {
+                if !is_label(label) {
+                    return Err(Error::parse(17, source, label, "a label name"));
+                }
+                if labels.insert(label, addr).is_some() {
+                    return Err(Error::parse(17, source, label, "a label not already used"));
+                }
+            }
+            if !line.code.is_empty() {
+                addr += 2;
+            }
+        }
+
+        let mut program = vec![];
+        for line in source.lines().map(split_line) {
+            let mut tokens = line.code.split_whitespace();
+            let Some(op) = tokens.next() else {
+                continue;
+            };
+            let op = mnemonic(op)?;
+
+            let operand = match tokens.next() {
+                None if op == Op::Bxc => 0,
+                None => return Err(Error::parse(17, source, line.code, "an operand")),
+                Some(t) => match t.parse::<u64>() {
+                    Ok(n) if n <= 7 => n,
+                    Ok(_) => return Err(Error::parse(17, source, t, "a 3-bit number")),
+                    Err(_) if op.takes_combo() && is_register(t) => match t {
+                        "a" => 4,
+                        "b" => 5,
+                        _ => 6,
+                    },
+                    Err(_) if op == Op::Jnz => match labels.get(t) {
+                        Some(&addr) if addr <= 7 => addr,
+                        Some(_) => {
+                            return Err(Error::parse(17, source, t, "a label at address 7 or less"))
+                        }
+                        None => return Err(Error::parse(17, source, t, "a defined label")),
+                    },
+                    Err(_) if op.takes_combo() => {
+                        return Err(Error::parse(17, source, t, "a register or a number"))
+                    }
+                    Err(_) => return Err(Error::parse(17, source, t, "a number")),
+                },
+            };
+            if let Some(extra) = tokens.next() {
+                return Err(Error::parse(17, source, extra, "the end of the line"));
+            }
+
+            program.extend([op.code(), operand]);
+        }
+
+        Ok(Machine::from_program(&program))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::run;
+    use super::*;
+
+    fn outputs(a: u64, source: &str) -> Vec<u64> {
+        let m = Machine {
+            a,
+            ..Machine::assemble(source).unwrap()
+        };
+        run(m, 1000).unwrap()
+    }
+
+    #[test]
+    fn assembles() {
+        let m = Machine::assemble(
+            "
+            ; the sample from part 2
+            top:
+                adv 3
+                out a       ; one octal digit
+                jnz top
+            ",
+        )
+        .unwrap();
+        assert_eq!(vec![0, 3, 5, 4, 3, 0], m.program);
+
+        // a label can share a line with its instruction, and be jumped to
+        // before it's defined
+        let m = Machine::assemble("jnz end\nbxc\nend: out 7").unwrap();
+        assert_eq!(vec![3, 4, 4, 0, 5, 7], m.program);
+    }
+
+    #[test]
+    fn round_trip() {
+        for program in [
+            &[0, 3, 5, 4, 3, 0][..],
+            &[2, 4, 1, 1, 7, 5, 1, 5, 4, 3, 0, 3, 5, 5, 3, 0],
+            &[3, 1, 5, 7, 4, 0, 1, 7, 6, 6, 3, 6],
+        ] {
+            let m = Machine::from_program(program);
+            let listing = m.disassemble().unwrap();
+            assert_eq!(m, Machine::assemble(&listing).unwrap(), "{listing}");
+        }
+
+        // the odd word at the end is only listed as a comment
+        let m = Machine::assemble("    bxc       ; 4: b ^= c\n; 6: trailing word 2\n");
+        assert_eq!(vec![4, 0], m.unwrap().program);
+    }
+
+    #[test]
+    fn errors() {
+        let err = |source| match Machine::assemble(source) {
+            Err(Error::Parse {
+                line,
+                column,
+                token,
+                expected,
+                ..
+            }) => (line, column, token, expected),
+            other => panic!("{other:?}"),
+        };
+
+        assert_eq!((2, 1, "mul".into(), "an instruction"), err("out a\nmul 3"));
+        assert_eq!((1, 5, "8".into(), "a 3-bit number"), err("bxl 8"));
+        assert_eq!((1, 5, "a".into(), "a number"), err("bxl a"));
+        assert_eq!((1, 5, "d".into(), "a register or a number"), err("out d"));
+        assert_eq!(
+            (1, 5, "nowhere".into(), "a defined label"),
+            err("jnz nowhere")
+        );
+        assert_eq!((1, 1, "out".into(), "an operand"), err("out"));
+        assert_eq!((1, 7, "b".into(), "the end of the line"), err("out a b"));
+        assert_eq!(
+            (2, 1, "x".into(), "a label not already used"),
+            err("x: bxc\nx: bxc")
+        );
+        assert_eq!((1, 1, "b".into(), "a label name"), err("b: bxc"));
+
+        let far = "jnz far\n".to_string() + &"bxc\n".repeat(4) + "far: out a";
+        assert_eq!(
+            (1, 5, "far".into(), "a label at address 7 or less"),
+            err(&far)
+        );
+    }
+
+    /// What each instruction does, one small program per behaviour.
+    #[test]
+    fn semantics() {
+        // adv divides a by 2 to the power of its combo operand
+        assert_eq!(vec![5], outputs(45, "adv 3\nout a"));
+        assert_eq!(vec![3], outputs(45, "bst 2\nadv b\nout a"));
+        // bdv and cdv do the same into b and c, leaving a alone
+        assert_eq!(vec![3, 5], outputs(45, "bdv 2\nout b\nout a"));
+        assert_eq!(vec![5, 5], outputs(45, "cdv 3\nout c\nout a"));
+        // shifting by more than 63 empties the register
+        assert_eq!(vec![0], outputs(u64::MAX, "bdv 0\nadv b\nout a"));
+
+        // bxl xors b with a literal, bxc with c whatever its operand
+        assert_eq!(vec![6], outputs(0, "bxl 6\nout b"));
+        assert_eq!(vec![3], outputs(6, "bxl 5\ncdv 0\nbxc 7\nout b"));
+        // bst keeps the low three bits
+        assert_eq!(vec![5], outputs(45, "bst a\nout b"));
+        assert_eq!(vec![3], outputs(0, "bst 3\nout b"));
+
+        // out only ever shows the low three bits
+        assert_eq!(vec![5, 1], outputs(45, "out a\nout 1"));
+
+        // jnz jumps while a is non-zero, and falls through when it's zero
+        let countdown = "top: out a\nadv 1\njnz top";
+        assert_eq!(vec![5, 6, 3, 1], outputs(13, countdown));
+        assert_eq!(vec![0, 1], outputs(0, "jnz skip\nout 0\nskip: out 1"));
+        assert_eq!(vec![1], outputs(1, "jnz skip\nout 0\nskip: out 1"));
+    }
+}
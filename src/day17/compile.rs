@@ -0,0 +1,195 @@
+use std::ops::ControlFlow;
+
+use super::{Fault, Machine, Op, Operand};
+
+/// Where an operand's value comes from: a constant, or a register by index.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Src {
+    Lit(u64),
+    Reg(usize),
+}
+
+/// An instruction with its operand already looked up.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Code {
+    /// `adv`, `bdv` and `cdv`: shift A right into a register.
+    Shift {
+        dst: usize,
+        by: Src,
+    },
+    Bxl(u64),
+    Bst(Src),
+    Jnz(usize),
+    Bxc,
+    Out(Src),
+    /// What the interpreter would fail with on getting here.
+    Fault(Fault),
+}
+
+const A: usize = 0;
+const B: usize = 1;
+const C: usize = 2;
+
+/// A program decoded ahead of time, to run many times with different
+/// registers without looking at the raw words again. Every address gets
+/// its own entry, so jumping into the middle of an instruction behaves
+/// just as it does in the interpreter.
+#[derive(Clone, Debug)]
+pub struct Compiled {
+    code: Vec<Code>,
+}
+
+impl Machine {
+    pub fn compile(&self) -> Compiled {
+        let mut probe = self.clone();
+        let code = (0..self.program.len())
+            .map(|ip| {
+                probe.ip = ip;
+                let i = match probe.fetch() {
+                    Ok(Some(i)) => i,
+                    Ok(None) => unreachable!("ip is inside the program"),
+                    Err(f) => return Code::Fault(f),
+                };
+                let src = match i.operand {
+                    Operand::Lit(n) => Src::Lit(n),
+                    Operand::A => Src::Reg(A),
+                    Operand::B => Src::Reg(B),
+                    Operand::C => Src::Reg(C),
+                    Operand::Reserved => return Code::Fault(Fault::ReservedOperand { ip }),
+                };
+                let lit = || match src {
+                    Src::Lit(n) => n,
+                    Src::Reg(_) => unreachable!("only combo operands name registers"),
+                };
+                match i.op {
+                    Op::Adv => Code::Shift { dst: A, by: src },
+                    Op::Bdv => Code::Shift { dst: B, by: src },
+                    Op::Cdv => Code::Shift { dst: C, by: src },
+                    Op::Bxl => Code::Bxl(lit()),
+                    Op::Bst => Code::Bst(src),
+                    Op::Jnz => Code::Jnz(lit() as usize),
+                    Op::Bxc => Code::Bxc,
+                    Op::Out => Code::Out(src),
+                }
+            })
+            .collect();
+
+        Compiled { code }
+    }
+}
+
+impl Compiled {
+    /// Run from the start with the given registers, handing each output to
+    /// `out` until the program halts or `out` says to stop.
+    pub fn run_with(
+        &self,
+        a: u64,
+        b: u64,
+        c: u64,
+        mut out: impl FnMut(u64) -> ControlFlow<()>,
+    ) -> Result<(), Fault> {
+        let mut regs = [a, b, c];
+        let mut ip = 0;
+
+        while let Some(code) = self.code.get(ip) {
+            let value = |src| match src {
+                Src::Lit(n) => n,
+                Src::Reg(r) => regs[r],
+            };
+
+            ip += 2;
+            match *code {
+                Code::Shift { dst, by } => {
+                    let by = u32::try_from(value(by)).unwrap_or(u32::MAX);
+                    regs[dst] = regs[A].checked_shr(by).unwrap_or(0);
+                }
+                Code::Bxl(n) => regs[B] ^= n,
+                Code::Bst(src) => regs[B] = value(src) & 0b111,
+                Code::Jnz(target) => {
+                    if regs[A] != 0 {
+                        ip = target;
+                    }
+                }
+                Code::Bxc => regs[B] ^= regs[C],
+                Code::Out(src) => {
+                    if out(value(src) & 0b111).is_break() {
+                        return Ok(());
+                    }
+                }
+                Code::Fault(ref f) => return Err(f.clone()),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Everything the program outputs before it halts.
+    pub fn run(&self, a: u64, b: u64, c: u64) -> Result<Vec<u64>, Fault> {
+        let mut outputs = vec![];
+        self.run_with(a, b, c, |v| {
+            outputs.push(v);
+            ControlFlow::Continue(())
+        })?;
+        Ok(outputs)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{Debugger, Stop};
+    use super::*;
+
+    #[test]
+    fn matches_interpreter() {
+        // random programs and registers, compared wherever the interpreter
+        // finishes in reasonable time
+        let mut next = crate::test::lcg(0x2024);
+
+        let mut compared = 0;
+        for _ in 0..5000 {
+            let len = 1 + next() as usize % 16;
+            let program: Vec<_> = (0..len).map(|_| next() % 8).collect();
+            let m = Machine {
+                a: next() << (next() % 32),
+                b: next() % 64,
+                c: next() % 64,
+                ..Machine::from_program(&program)
+            };
+
+            let mut d = Debugger::new(m.clone()).max_cycles(10_000);
+            let expected = match d.run() {
+                Ok(Stop::Halted) => Ok(d.output().to_vec()),
+                Ok(_) => continue,
+                Err(f) => Err(f),
+            };
+            let got = m.compile().run(m.a, m.b, m.c);
+            assert_eq!(expected, got, "{m:?}");
+            compared += 1;
+        }
+        assert!(compared > 3000);
+    }
+
+    #[test]
+    fn stops_early() {
+        let m = Machine::assemble("top: out a\nadv 1\njnz top").unwrap();
+        let mut seen = vec![];
+        let res = m.compile().run_with(13, 0, 0, |v| {
+            seen.push(v);
+            match seen.len() {
+                2 => ControlFlow::Break(()),
+                _ => ControlFlow::Continue(()),
+            }
+        });
+        assert_eq!(Ok(()), res);
+        assert_eq!(vec![5, 6], seen);
+
+        // jumping into the middle of an instruction, which is `out a` and
+        // then an opcode with nothing after it
+        let m = Machine::from_program(&[3, 3, 5, 5, 4, 0]);
+        assert_eq!(
+            Err(Fault::MissingOperand { ip: 5 }),
+            m.compile().run(1, 0, 0)
+        );
+        assert_eq!(Ok(vec![0]), m.compile().run(0, 0, 0));
+    }
+}
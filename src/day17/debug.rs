@@ -0,0 +1,270 @@
+use std::fmt::Display;
+
+use super::{Event, Fault, Instr, Machine};
+
+/// Somewhere for `Debugger::run` to stop before carrying on.
+pub enum Breakpoint {
+    /// Just before the instruction at this address runs.
+    Ip(usize),
+    /// Just before any instruction, if the machine is in this state.
+    When(Box<dyn Fn(&Machine) -> bool>),
+}
+
+impl Breakpoint {
+    fn hit(&self, m: &Machine) -> bool {
+        match self {
+            Breakpoint::Ip(ip) => m.ip == *ip,
+            Breakpoint::When(cond) => cond(m),
+        }
+    }
+}
+
+/// Why `Debugger::run` came back.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stop {
+    Halted,
+    /// The breakpoint at this index, in the order they were added.
+    Breakpoint(usize),
+    /// The cycle limit was reached with the program still going.
+    CycleLimit,
+}
+
+/// One instruction that ran, and the registers after it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceEntry {
+    pub cycle: u64,
+    pub instr: Instr,
+    pub a: u64,
+    pub b: u64,
+    pub c: u64,
+    pub output: Option<u64>,
+}
+
+impl Display for TraceEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:>6} {:>3}: {:<10} a={} b={} c={}",
+            self.cycle,
+            self.instr.addr,
+            self.instr.to_string(),
+            self.a,
+            self.b,
+            self.c
+        )?;
+        if let Some(o) = self.output {
+            write!(f, " out {o}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs a `Machine` under control: a step at a time, up to breakpoints,
+/// or for a limited number of cycles, optionally keeping a trace.
+pub struct Debugger {
+    pub machine: Machine,
+    breakpoints: Vec<Breakpoint>,
+    max_cycles: Option<u64>,
+    cycles: u64,
+    output: Vec<u64>,
+    trace: Option<Vec<TraceEntry>>,
+    /// Where the last `run` stopped at a breakpoint, if nothing has run
+    /// since.
+    stopped_at: Option<usize>,
+}
+
+impl Debugger {
+    pub fn new(machine: Machine) -> Debugger {
+        Debugger {
+            machine,
+            breakpoints: vec![],
+            max_cycles: None,
+            cycles: 0,
+            output: vec![],
+            trace: None,
+            stopped_at: None,
+        }
+    }
+
+    pub fn break_at(mut self, ip: usize) -> Self {
+        self.breakpoints.push(Breakpoint::Ip(ip));
+        self
+    }
+
+    pub fn break_when(mut self, cond: impl Fn(&Machine) -> bool + 'static) -> Self {
+        self.breakpoints.push(Breakpoint::When(Box::new(cond)));
+        self
+    }
+
+    /// Stop `run` once this many instructions have run in total.
+    pub fn max_cycles(mut self, n: u64) -> Self {
+        self.max_cycles = Some(n);
+        self
+    }
+
+    /// Record every instruction from here on.
+    pub fn traced(mut self) -> Self {
+        self.trace.get_or_insert_with(Vec::new);
+        self
+    }
+
+    /// Run one instruction, whatever breakpoints or limits say.
+    pub fn step(&mut self) -> Result<Event, Fault> {
+        self.stopped_at = None;
+        let instr = self.machine.fetch()?;
+        let event = self.machine.step()?;
+        let Some(instr) = instr else {
+            return Ok(event);
+        };
+
+        self.cycles += 1;
+        let output = match event {
+            Event::Output(v) => {
+                self.output.push(v);
+                Some(v)
+            }
+            _ => None,
+        };
+        if let Some(trace) = &mut self.trace {
+            let m = &self.machine;
+            trace.push(TraceEntry {
+                cycle: self.cycles,
+                instr,
+                a: m.a,
+                b: m.b,
+                c: m.c,
+                output,
+            });
+        }
+
+        Ok(event)
+    }
+
+    /// Run until the program halts, a breakpoint is hit or the cycle limit
+    /// is reached. Breakpoints are checked before every instruction, the
+    /// first included, except that calling this again right after stopping
+    /// at one carries on past it.
+    pub fn run(&mut self) -> Result<Stop, Fault> {
+        let mut resume_at = self.stopped_at.take();
+        loop {
+            if self.max_cycles.is_some_and(|n| self.cycles >= n) {
+                return Ok(Stop::CycleLimit);
+            }
+            if resume_at.take() != Some(self.machine.ip) {
+                if let Some(i) = self.breakpoints.iter().position(|b| b.hit(&self.machine)) {
+                    self.stopped_at = Some(self.machine.ip);
+                    return Ok(Stop::Breakpoint(i));
+                }
+            }
+
+            if self.step()? == Event::Halted {
+                return Ok(Stop::Halted);
+            }
+        }
+    }
+
+    /// How many instructions have run.
+    pub fn cycles(&self) -> u64 {
+        self.cycles
+    }
+
+    pub fn output(&self) -> &[u64] {
+        &self.output
+    }
+
+    /// Everything recorded since `traced` was called; empty if it wasn't.
+    pub fn trace(&self) -> &[TraceEntry] {
+        self.trace.as_deref().unwrap_or(&[])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn machine(a: u64, program: &[u64]) -> Machine {
+        Machine {
+            a,
+            ..Machine::from_program(program)
+        }
+    }
+
+    const SAMPLE: [u64; 6] = [0, 1, 5, 4, 3, 0];
+
+    #[test]
+    fn stepping() {
+        let mut d = Debugger::new(machine(729, &SAMPLE)).traced();
+        assert_eq!(Ok(Event::Ran), d.step());
+        assert_eq!(364, d.machine.a);
+        assert_eq!(Ok(Event::Output(4)), d.step());
+        assert_eq!(Ok(Event::Ran), d.step());
+        assert_eq!(0, d.machine.ip);
+
+        assert_eq!(Ok(Stop::Halted), d.run());
+        assert_eq!(&[4, 6, 3, 5, 6, 3, 5, 2, 1, 0], d.output());
+        assert_eq!(30, d.cycles());
+        assert_eq!(Ok(Event::Halted), d.step());
+        assert_eq!(30, d.trace().len());
+        assert_eq!(
+            "     2   2: out a      a=364 b=0 c=0 out 4",
+            d.trace()[1].to_string()
+        );
+    }
+
+    #[test]
+    fn breakpoints() {
+        let mut d = Debugger::new(machine(729, &SAMPLE))
+            .break_at(4)
+            .break_when(|m| m.a < 10);
+        assert_eq!(Ok(Stop::Breakpoint(0)), d.run());
+        assert_eq!((4, 1), (d.machine.ip, d.output().len()));
+        assert_eq!(Ok(Stop::Breakpoint(0)), d.run());
+        assert_eq!(2, d.output().len());
+
+        // a = 729 / 2^n drops below 10 after the seventh halving
+        let mut d = Debugger::new(machine(729, &SAMPLE)).break_when(|m| m.a < 10);
+        assert_eq!(Ok(Stop::Breakpoint(0)), d.run());
+        assert_eq!(5, d.machine.a);
+        assert_eq!(6, d.output().len());
+
+        // breakpoints apply before the very first instruction too
+        let mut d = Debugger::new(machine(729, &SAMPLE)).break_at(0);
+        assert_eq!(Ok(Stop::Breakpoint(0)), d.run());
+        assert_eq!(0, d.cycles());
+        assert_eq!(Ok(Stop::Breakpoint(0)), d.run());
+        assert_eq!(3, d.cycles());
+
+        let mut d = Debugger::new(machine(729, &SAMPLE)).break_when(|m| m.a > 100);
+        assert_eq!(Ok(Stop::Breakpoint(0)), d.run());
+        assert_eq!(0, d.cycles());
+        // stepping off the breakpoint by hand means it's checked again
+        d.step().unwrap();
+        d.machine.ip = 0;
+        assert_eq!(Ok(Stop::Breakpoint(0)), d.run());
+        assert_eq!(1, d.cycles());
+    }
+
+    #[test]
+    fn limits_and_faults() {
+        // jumps back to itself forever
+        let mut d = Debugger::new(machine(1, &[3, 0])).max_cycles(1000);
+        assert_eq!(Ok(Stop::CycleLimit), d.run());
+        assert_eq!(1000, d.cycles());
+
+        let mut d = Debugger::new(machine(1, &[5, 7]));
+        assert_eq!(Err(Fault::ReservedOperand { ip: 0 }), d.run());
+
+        let mut d = Debugger::new(machine(1, &[1, 2, 5]));
+        assert_eq!(Err(Fault::MissingOperand { ip: 2 }), d.run());
+
+        let mut d = Debugger::new(machine(1, &[9, 0]));
+        assert_eq!(Err(Fault::BadOpcode { ip: 0, code: 9 }), d.run());
+
+        // shifting by more than the width of a register just empties it
+        let mut m = machine(u64::MAX, &[0, 5]);
+        m.b = 200;
+        let mut d = Debugger::new(m);
+        assert_eq!(Ok(Stop::Halted), d.run());
+        assert_eq!(0, d.machine.a);
+    }
+}
@@ -0,0 +1,218 @@
+use std::{collections::BTreeSet, fmt::Display};
+
+use super::{decode, Fault, Instr, Machine, Op, Operand};
+
+/// Addresses that some `jnz` can land on, if they start an instruction.
+fn jump_targets(instrs: &[Instr]) -> BTreeSet<usize> {
+    instrs
+        .iter()
+        .filter(|i| i.op == Op::Jnz)
+        .filter_map(|i| match i.operand {
+            Operand::Lit(t) => usize::try_from(t).ok(),
+            _ => None,
+        })
+        .filter(|&t| t % 2 == 0 && t / 2 < instrs.len())
+        .collect()
+}
+
+fn label(addr: usize) -> String {
+    format!("L{addr}")
+}
+
+/// A combo operand as written in assembly: its literal value or register.
+fn combo(operand: Operand) -> String {
+    match operand {
+        Operand::Lit(n) => n.to_string(),
+        Operand::A => "a".to_string(),
+        Operand::B => "b".to_string(),
+        Operand::C => "c".to_string(),
+        Operand::Reserved => "7".to_string(),
+    }
+}
+
+/// The instruction as assembly, with `jnz` going to a label where it can.
+fn text(i: &Instr, targets: &BTreeSet<usize>) -> String {
+    let operand = match (i.op, i.operand) {
+        (Op::Jnz, Operand::Lit(t)) if targets.contains(&(t as usize)) => label(t as usize),
+        // the operand is read but ignored, so only show it if it's there
+        (Op::Bxc, Operand::Lit(0)) => return i.op.mnemonic().to_string(),
+        (_, o) => combo(o),
+    };
+    format!("{} {operand}", i.op.mnemonic())
+}
+
+/// What the instruction does, C-ish.
+fn pseudo(i: &Instr, targets: &BTreeSet<usize>) -> String {
+    let x = match i.operand {
+        Operand::Reserved => "?".to_string(),
+        o => combo(o),
+    };
+    let mod8 = |x: &str| match i.operand {
+        Operand::A | Operand::B | Operand::C => format!("{x} % 8"),
+        _ => x.to_string(),
+    };
+
+    match i.op {
+        Op::Adv => format!("a >>= {x}"),
+        Op::Bdv => format!("b = a >> {x}"),
+        Op::Cdv => format!("c = a >> {x}"),
+        Op::Bxl => format!("b ^= {x}"),
+        Op::Bst => format!("b = {}", mod8(&x)),
+        Op::Jnz => match i.operand {
+            Operand::Lit(t) if targets.contains(&(t as usize)) => {
+                format!("if a != 0 goto {}", label(t as usize))
+            }
+            _ => format!("if a != 0 goto {x}"),
+        },
+        Op::Bxc => "b ^= c".to_string(),
+        Op::Out => format!("out({})", mod8(&x)),
+    }
+}
+
+/// The instruction as assembly, without labels.
+impl Display for Instr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&text(self, &BTreeSet::new()))
+    }
+}
+
+impl Machine {
+    /// The program as assembly, one instruction per line, with each jump
+    /// target labelled and each instruction's address and meaning in a
+    /// comment.
+    pub fn disassemble(&self) -> Result<String, Fault> {
+        let instrs = decode(&self.program)?;
+        let targets = jump_targets(&instrs);
+
+        let mut out = String::new();
+        for i in &instrs {
+            if targets.contains(&i.addr) {
+                out += &format!("{}:\n", label(i.addr));
+            }
+            let comment = format!("{}: {}", i.addr, pseudo(i, &targets));
+            let comment = match i.operand {
+                Operand::Reserved => comment + " (reserved operand)",
+                _ => comment,
+            };
+            out += &format!("    {:<10}; {comment}\n", text(i, &targets));
+        }
+        if self.program.len() % 2 == 1 {
+            let last = self.program.len() - 1;
+            out += &format!("; {last}: trailing word {}\n", self.program[last]);
+        }
+
+        Ok(out)
+    }
+
+    /// The program as pseudo-code. The usual shape, a single loop that
+    /// jumps back to the start, is shown as a `do ... while`.
+    pub fn pseudocode(&self) -> Result<String, Fault> {
+        let instrs = decode(&self.program)?;
+        let targets = jump_targets(&instrs);
+
+        let is_loop = matches!(
+            instrs.last(),
+            Some(Instr {
+                op: Op::Jnz,
+                operand: Operand::Lit(0),
+                ..
+            })
+        ) && targets.len() == 1;
+
+        let (body, indent) = if is_loop {
+            (&instrs[..instrs.len() - 1], "    ")
+        } else {
+            (&instrs[..], "")
+        };
+
+        let mut out = String::new();
+        if is_loop {
+            out += "do {\n";
+        }
+        for i in body {
+            if !is_loop && targets.contains(&i.addr) {
+                out += &format!("{}:\n", label(i.addr));
+            }
+            out += &format!("{indent}{}\n", pseudo(i, &targets));
+        }
+        if is_loop {
+            out += "} while a != 0\n";
+        }
+
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn listing() {
+        let m = Machine::from_program(&[0, 3, 5, 4, 3, 0]);
+        assert_eq!(
+            "L0:
+    adv 3     ; 0: a >>= 3
+    out a     ; 2: out(a % 8)
+    jnz L0    ; 4: if a != 0 goto L0
+",
+            m.disassemble().unwrap()
+        );
+        assert_eq!(
+            "do {
+    a >>= 3
+    out(a % 8)
+} while a != 0
+",
+            m.pseudocode().unwrap()
+        );
+    }
+
+    #[test]
+    fn typical_input() {
+        // the usual shape of a real input: one loop, shifting a by 3 once
+        let m = Machine::from_program(&[2, 4, 1, 1, 7, 5, 1, 5, 4, 3, 0, 3, 5, 5, 3, 0]);
+        assert_eq!(
+            "do {
+    b = a % 8
+    b ^= 1
+    c = a >> b
+    b ^= 5
+    b ^= c
+    a >>= 3
+    out(b % 8)
+} while a != 0
+",
+            m.pseudocode().unwrap()
+        );
+        assert!(m
+            .disassemble()
+            .unwrap()
+            .contains("    bxc 3     ; 8: b ^= c\n"));
+    }
+
+    #[test]
+    fn odd_cases() {
+        // a jump into the middle of an instruction, the reserved combo
+        // operand and a dangling opcode
+        let m = Machine::from_program(&[3, 1, 5, 7, 4, 0, 2]);
+        assert_eq!(
+            "    jnz 1     ; 0: if a != 0 goto 1
+    out 7     ; 2: out(?) (reserved operand)
+    bxc       ; 4: b ^= c
+; 6: trailing word 2
+",
+            m.disassemble().unwrap()
+        );
+        assert_eq!(
+            "if a != 0 goto 1\nout(?)\nb ^= c\n",
+            m.pseudocode().unwrap()
+        );
+
+        // a word that isn't an opcode where an instruction should start
+        let m = Machine::from_program(&[5, 4, 9, 0]);
+        let bad = Err(Fault::BadOpcode { ip: 2, code: 9 });
+        assert_eq!(bad, m.disassemble());
+        assert_eq!(bad, m.pseudocode());
+    }
+}
@@ -0,0 +1,510 @@
+//! Finding the initial A that makes a program output itself.
+//!
+//! Most programs are one loop that outputs once per pass, shifts A right
+//! by a fixed amount and starts afresh with B and C each time round. Then
+//! each output depends only on A's value at the top of its pass, and A can
+//! be built a digit at a time from the last output backwards. Anything
+//! else gets a slower search over A's bits from the bottom up, running the
+//! program on partly-known values to rule out whole subtrees at once.
+
+use std::{collections::HashSet, fmt::Display, ops::ControlFlow};
+
+use super::{decode, Compiled, Event, Fault, Instr, Machine, Op, Operand};
+use crate::Error;
+
+/// The parts of a program's structure that the quine search relies on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Shape {
+    /// A single loop back to the start that outputs once per pass, shifts
+    /// A right by `shift` bits, and sets B and C before reading them.
+    DigitLoop { shift: u32 },
+    /// Anything else.
+    Other,
+}
+
+/// Why there's no quine to report.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum QuineError {
+    /// Every possible A was ruled out.
+    NoSolution,
+    /// The bit search looked at this many partial values without
+    /// finishing.
+    GaveUp { nodes: u64 },
+    /// The program can't be decoded.
+    Fault(Fault),
+}
+
+impl Display for QuineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QuineError::NoSolution => f.write_str("no initial A makes the program output itself"),
+            QuineError::GaveUp { nodes } => {
+                write!(f, "gave up looking for A after {nodes} partial values")
+            }
+            QuineError::Fault(fault) => write!(f, "{fault}"),
+        }
+    }
+}
+
+impl std::error::Error for QuineError {}
+
+impl From<QuineError> for Error {
+    fn from(e: QuineError) -> Error {
+        match e {
+            QuineError::NoSolution => Error::no_answer(17, "no initial A reproduces the program"),
+            QuineError::GaveUp { .. } => Error::no_answer(17, e.to_string()),
+            QuineError::Fault(fault) => fault.into(),
+        }
+    }
+}
+
+/// Registers that a combo operand reads, as (reads B, reads C).
+fn combo_reads(operand: Operand) -> (bool, bool) {
+    (operand == Operand::B, operand == Operand::C)
+}
+
+/// Whether B and C are each written before anything in `body` reads them.
+fn fresh_registers(body: &[Instr]) -> bool {
+    let (mut b_set, mut c_set) = (false, false);
+    for i in body {
+        let (reads_b, reads_c) = match i.op {
+            Op::Bxl => (true, false),
+            Op::Bxc => (true, true),
+            _ if i.op.takes_combo() => combo_reads(i.operand),
+            _ => (false, false),
+        };
+        if (reads_b && !b_set) || (reads_c && !c_set) {
+            return false;
+        }
+        match i.op {
+            Op::Bst | Op::Bdv | Op::Bxl | Op::Bxc => b_set = true,
+            Op::Cdv => c_set = true,
+            _ => {}
+        }
+    }
+    true
+}
+
+impl Machine {
+    /// Work out whether the program has the structure that the digit
+    /// search needs.
+    pub fn shape(&self) -> Result<Shape, Fault> {
+        let instrs = decode(&self.program)?;
+        let Some((last, body)) = instrs.split_last() else {
+            return Ok(Shape::Other);
+        };
+        let count = |op| body.iter().filter(|i| i.op == op).count();
+
+        let loops_to_start = last.op == Op::Jnz && last.operand == Operand::Lit(0);
+        let shift = body.iter().find_map(|i| match (i.op, i.operand) {
+            (Op::Adv, Operand::Lit(n @ 1..=3)) => Some(n as u32),
+            _ => None,
+        });
+
+        Ok(match shift {
+            Some(shift)
+                if loops_to_start
+                    && self.program.len().is_multiple_of(2)
+                    && count(Op::Jnz) == 0
+                    && count(Op::Adv) == 1
+                    && count(Op::Out) == 1
+                    && body.iter().all(|i| i.operand != Operand::Reserved)
+                    && fresh_registers(body) =>
+            {
+                Shape::DigitLoop { shift }
+            }
+            _ => Shape::Other,
+        })
+    }
+
+    /// The lowest positive initial A for which the program outputs a copy
+    /// of itself.
+    pub fn find_quine(&self) -> Result<u64, QuineError> {
+        match self.shape().map_err(QuineError::Fault)? {
+            Shape::DigitLoop { shift } => digits(self, shift),
+            Shape::Other => bits(self, BIT_SEARCH_BUDGET),
+        }
+    }
+}
+
+/// What one pass of a `DigitLoop` outputs when it starts with `a`.
+fn one_pass(code: &Compiled, a: u64) -> Option<u64> {
+    let mut first = None;
+    code.run_with(a, 0, 0, |v| {
+        first = Some(v);
+        ControlFlow::Break(())
+    })
+    .ok()?;
+    first
+}
+
+/// Build A from its most significant digit down: the pass that outputs
+/// `target[k]` sees A shifted right by `k` digits, so each digit only has
+/// to be checked against one output.
+fn digits(m: &Machine, shift: u32) -> Result<u64, QuineError> {
+    fn go(code: &Compiled, shift: u32, target: &[u64], current: u64) -> Option<u64> {
+        let Some((&want, rest)) = target.split_last() else {
+            return Some(current);
+        };
+        for digit in 0..1 << shift {
+            let next = current.checked_shl(shift)? | digit;
+            if next == 0 || one_pass(code, next) != Some(want) {
+                continue;
+            }
+            if let Some(a) = go(code, shift, rest, next) {
+                return Some(a);
+            }
+        }
+        None
+    }
+
+    go(&m.compile(), shift, &m.program, 0).ok_or(QuineError::NoSolution)
+}
+
+/// How many partial values the bit search will look at.
+const BIT_SEARCH_BUDGET: u64 = 2_000_000;
+
+/// How long a concrete or partial run may go before it's assumed to hang.
+const MAX_CYCLES: u64 = 100_000;
+
+/// A 64-bit value of which only the bits in `known` are certain. Bits of
+/// `value` outside `known` are always zero.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct Bits {
+    known: u64,
+    value: u64,
+}
+
+impl Bits {
+    fn exact(value: u64) -> Bits {
+        Bits { known: !0, value }
+    }
+
+    fn get(self) -> Option<u64> {
+        (self.known == !0).then_some(self.value)
+    }
+
+    fn nonzero(self) -> Option<bool> {
+        if self.value != 0 {
+            Some(true)
+        } else if self.known == !0 {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    fn xor(self, other: Bits) -> Bits {
+        let known = self.known & other.known;
+        Bits {
+            known,
+            value: (self.value ^ other.value) & known,
+        }
+    }
+
+    fn low3(self) -> Bits {
+        Bits {
+            known: self.known | !0b111,
+            value: self.value & 0b111,
+        }
+    }
+
+    /// The bits that are the same whichever of the two it is.
+    fn join(self, other: Bits) -> Bits {
+        let known = self.known & other.known & !(self.value ^ other.value);
+        Bits {
+            known,
+            value: self.value & known,
+        }
+    }
+
+    fn shr(self, n: u64) -> Bits {
+        if n >= 64 {
+            return Bits::exact(0);
+        }
+        Bits {
+            known: (self.known >> n) | !(!0 >> n),
+            value: self.value >> n,
+        }
+    }
+
+    /// Shift right by an amount that may itself be partly unknown, trying
+    /// every value it could be when there are few enough.
+    fn shr_by(self, n: Bits) -> Bits {
+        if let Some(n) = n.get() {
+            return self.shr(n);
+        }
+        let unknown = !n.known;
+        if unknown.count_ones() > 6 {
+            return Bits { known: 0, value: 0 };
+        }
+
+        let mut out: Option<Bits> = None;
+        let mut sub = 0u64;
+        loop {
+            let r = self.shr(n.value | sub);
+            out = Some(out.map_or(r, |o| o.join(r)));
+            // next subset of the unknown bits
+            sub = sub.wrapping_sub(unknown) & unknown;
+            if sub == 0 {
+                break;
+            }
+        }
+        out.expect("at least one shift was tried")
+    }
+}
+
+/// Run the program on a partly-known A and report whether it could still
+/// output `target`: `false` only if it certainly can't. `probe` is only
+/// used to fetch instructions.
+fn could_match(probe: &mut Machine, a: Bits, target: &[u64]) -> bool {
+    let (mut a, mut b, mut c) = (a, Bits::exact(probe.b), Bits::exact(probe.c));
+    let mut out = 0;
+    // the state at each jump taken, to notice going round forever
+    let mut seen = HashSet::new();
+    probe.ip = 0;
+
+    for _ in 0..MAX_CYCLES {
+        // a fault means the program never finishes, so never matches
+        let Ok(fetched) = probe.fetch() else {
+            return false;
+        };
+        let Some(i) = fetched else {
+            return out == target.len();
+        };
+        let value = match i.operand {
+            Operand::Lit(n) => Bits::exact(n),
+            Operand::A => a,
+            Operand::B => b,
+            Operand::C => c,
+            Operand::Reserved => return false,
+        };
+
+        probe.ip += 2;
+        match i.op {
+            Op::Adv => a = a.shr_by(value),
+            Op::Bdv => b = a.shr_by(value),
+            Op::Cdv => c = a.shr_by(value),
+            Op::Bxl => b = b.xor(value),
+            Op::Bst => b = value.low3(),
+            Op::Bxc => b = b.xor(c),
+            Op::Out => {
+                match (value.low3().get(), target.get(out)) {
+                    (_, None) => return false,
+                    (Some(v), Some(&t)) if v != t => return false,
+                    _ => {}
+                }
+                out += 1;
+            }
+            Op::Jnz => match a.nonzero() {
+                Some(true) => {
+                    probe.ip = value.get().expect("jnz takes a literal") as usize;
+                    if !seen.insert((probe.ip, a, b, c)) {
+                        return false;
+                    }
+                }
+                Some(false) => {}
+                // can't tell which way it goes, so can't rule anything out
+                None => return true,
+            },
+        }
+    }
+
+    true
+}
+
+/// Whether the program, started with `a`, outputs exactly itself.
+fn is_quine(m: &Machine, a: u64) -> bool {
+    let mut m = m.clone();
+    m.a = a;
+    let mut out = 0;
+    for _ in 0..MAX_CYCLES {
+        match m.step() {
+            Ok(Event::Ran) => {}
+            Ok(Event::Output(v)) => {
+                if m.program.get(out) != Some(&v) {
+                    return false;
+                }
+                out += 1;
+            }
+            Ok(Event::Halted) => return out == m.program.len(),
+            Err(_) => return false,
+        }
+    }
+    false
+}
+
+/// Fix A's bits from the least significant up, abandoning any prefix that
+/// the partial run shows can't produce the program, and checking each
+/// prefix as a complete value on the way.
+fn bits(m: &Machine, budget: u64) -> Result<u64, QuineError> {
+    struct Search<'a> {
+        m: &'a Machine,
+        probe: Machine,
+        nodes: u64,
+        budget: u64,
+        best: Option<u64>,
+    }
+
+    impl Search<'_> {
+        fn go(&mut self, k: u32, low: u64) -> Result<(), QuineError> {
+            // anything found from here on is bigger than the best so far
+            if self.best.is_some_and(|b| k > 0 && b < 1 << (k - 1)) {
+                return Ok(());
+            }
+            self.nodes += 1;
+            if self.nodes > self.budget {
+                return Err(QuineError::GaveUp { nodes: self.nodes });
+            }
+
+            let known = if k == 64 { !0 } else { (1u64 << k) - 1 };
+            let a = Bits { known, value: low };
+            if !could_match(&mut self.probe, a, &self.m.program) {
+                return Ok(());
+            }
+            // each value is checked once, at the length of its top bit
+            if k > 0 && low >> (k - 1) == 1 && is_quine(self.m, low) {
+                self.best = Some(self.best.map_or(low, |b| b.min(low)));
+            }
+            if k == 64 {
+                return Ok(());
+            }
+
+            self.go(k + 1, low)?;
+            self.go(k + 1, low | 1 << k)
+        }
+    }
+
+    let mut s = Search {
+        m,
+        probe: m.clone(),
+        nodes: 0,
+        budget,
+        best: None,
+    };
+    s.go(0, 0)?;
+    s.best.ok_or(QuineError::NoSolution)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn shapes() {
+        let sample = Machine::from_program(&[0, 3, 5, 4, 3, 0]);
+        assert_eq!(Ok(Shape::DigitLoop { shift: 3 }), sample.shape());
+
+        let typical = Machine::from_program(&[2, 4, 1, 1, 7, 5, 1, 5, 4, 3, 0, 3, 5, 5, 3, 0]);
+        assert_eq!(Ok(Shape::DigitLoop { shift: 3 }), typical.shape());
+
+        // b ^= 0 reads b before anything has set it, so it carries over
+        assert_eq!(
+            Ok(Shape::Other),
+            Machine::from_program(&[0, 3, 1, 0, 5, 4, 3, 0]).shape()
+        );
+        // two outputs a pass
+        assert_eq!(
+            Ok(Shape::Other),
+            Machine::from_program(&[0, 3, 5, 4, 5, 4, 3, 0]).shape()
+        );
+        // no loop at all
+        assert_eq!(Ok(Shape::Other), Machine::from_program(&[5, 4]).shape());
+        // shifting by a register
+        assert_eq!(
+            Ok(Shape::Other),
+            Machine::from_program(&[2, 4, 0, 5, 5, 4, 3, 0]).shape()
+        );
+    }
+
+    #[test]
+    fn both_searches_agree() {
+        let sample = Machine::from_program(&[0, 3, 5, 4, 3, 0]);
+        assert_eq!(Ok(117440), digits(&sample, 3));
+        assert_eq!(Ok(117440), bits(&sample, BIT_SEARCH_BUDGET));
+        assert_eq!(Ok(117440), sample.find_quine());
+
+        // the usual shape with varying constants, some of which have no
+        // quine at all
+        let mut found = 0;
+        for (x, y) in [(1, 5), (2, 7), (3, 6), (5, 3), (6, 4), (7, 1)] {
+            let m = Machine::from_program(&[2, 4, 1, x, 7, 5, 1, y, 4, 3, 0, 3, 5, 5, 3, 0]);
+            let d = digits(&m, 3);
+            assert_eq!(d, bits(&m, BIT_SEARCH_BUDGET), "bxl {x}, bxl {y}");
+            if let Ok(a) = d {
+                assert!(is_quine(&m, a));
+                found += 1;
+            }
+        }
+        assert!(found > 0);
+    }
+
+    #[test]
+    fn other_shapes() {
+        // outputs a's octal digits from the second one up, but keeps b
+        // from one pass to the next; A is the program written backwards in
+        // octal, moved up a digit
+        let m = Machine::from_program(&[0, 3, 1, 0, 5, 4, 3, 0]);
+        assert_eq!(Ok(0o34501300), m.find_quine());
+
+        // always outputs a single 0, so can't reproduce two words
+        assert_eq!(
+            Err(QuineError::NoSolution),
+            Machine::from_program(&[5, 0]).find_quine()
+        );
+        // a word that isn't an opcode
+        let bad = Machine::from_program(&[0, 3, 9, 4, 3, 0]);
+        assert_eq!(Err(Fault::BadOpcode { ip: 2, code: 9 }), bad.shape());
+        assert_eq!(
+            Err(QuineError::Fault(Fault::BadOpcode { ip: 2, code: 9 })),
+            bad.find_quine()
+        );
+        // never halts
+        assert_eq!(
+            Err(QuineError::NoSolution),
+            Machine::from_program(&[3, 0]).find_quine()
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            Error::no_answer(17, "gave up looking for A after 12 partial values"),
+            Error::from(QuineError::GaveUp { nodes: 12 })
+        );
+        assert_eq!(
+            Err(Error::no_answer(
+                17,
+                "gave up looking for A after 3 partial values"
+            )),
+            bits(&Machine::from_program(&[0, 3, 5, 4, 3, 0]), 2).map_err(Error::from)
+        );
+    }
+
+    #[test]
+    fn partial_values() {
+        let half = Bits {
+            known: 0xff,
+            value: 0x2c,
+        };
+        assert_eq!(None, half.get());
+        assert_eq!(Some(true), half.nonzero());
+        assert_eq!(Some(0b100), half.low3().get());
+        assert_eq!(
+            Bits {
+                known: 0x0f | !(!0 >> 4),
+                value: 0x2
+            },
+            half.shr(4)
+        );
+
+        // shifting 0b1100 right by 2 or 3 leaves a 1 in the lowest bit
+        let amount = Bits {
+            known: !1,
+            value: 2,
+        };
+        let r = Bits::exact(0b1100).shr_by(amount);
+        assert_eq!(1, r.value & 1);
+        assert_eq!(0, r.known & 0b10);
+    }
+}
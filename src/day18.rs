@@ -1,21 +1,15 @@
-use std::collections::{BinaryHeap, HashSet};
+use std::collections::HashSet;
 
-use crate::Day;
+use crate::{astar, params, parse, Day, Error};
 
 type P = (i32, i32);
 
-#[derive(Debug, Eq, PartialEq, Clone, Copy)]
-struct QueueState(P, i32);
-
-impl Ord for QueueState {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        other.1.cmp(&self.1)
-    }
-}
-
-impl PartialOrd for QueueState {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
+params! {
+    pub struct Day18Params {
+        pub width: i32 = 71,
+        pub height: i32 = 71,
+        /// how many bytes have fallen for part 1
+        pub bytes: usize = 1024,
     }
 }
 
@@ -27,117 +21,111 @@ pub struct Day18 {
 }
 
 impl Day18 {
-    fn parse(input: &str, size: Option<P>) -> Self {
-        let pairs: Vec<(i32, i32)> = input
-            .trim()
-            .lines()
-            .map(|l| {
-                let (left, right) = l.trim().split_once(",").unwrap();
-
-                (left.parse().unwrap(), right.parse().unwrap())
-            })
-            .collect();
-
-        let size = size.unwrap_or((71, 71));
-        Day18 {
+    fn parse(input: &str, params: &Day18Params) -> Result<Self, Error> {
+        let pairs = parse::lines(input)
+            .map(|l| parse::pair(l, ",").ok_or_else(|| Error::parse(18, input, l, "'x,y'")))
+            .collect::<Result<Vec<P>, _>>()?;
+
+        let size = (params.width, params.height);
+        Ok(Day18 {
             bytes: pairs,
             size,
             start: (0, 0),
             end: (size.0 - 1, size.1 - 1),
-        }
+        })
     }
 
-    fn dijkstra(s: P, e: P, obstacles: &HashSet<P>, bounds: P) -> i32 {
-        let mut q = BinaryHeap::<QueueState>::new();
-        let mut seen = HashSet::<P>::new();
+    /// Fewest steps from `s` to `e`, or `None` if the way is blocked.
+    fn shortest(s: P, e: P, obstacles: &HashSet<P>, bounds: P) -> Option<u64> {
+        let open = |p: &P| {
+            (0..bounds.0).contains(&p.0) && (0..bounds.1).contains(&p.1) && !obstacles.contains(p)
+        };
+        if !open(&s) {
+            return None;
+        }
 
-        q.push(QueueState(s, 0));
+        let neighbours = |p: &P| {
+            [(-1, 0), (1, 0), (0, -1), (0, 1)]
+                .map(|(dx, dy)| (p.0 + dx, p.1 + dy))
+                .into_iter()
+                .filter(open)
+                .map(|n| (n, 1))
+        };
+        let manhattan = |p: &P| p.0.abs_diff(e.0) as u64 + p.1.abs_diff(e.1) as u64;
 
-        loop {
-            let Some(QueueState(p, cost)) = q.pop() else {
-                break;
-            };
+        astar(s, neighbours, manhattan, |p| *p == e).map(|(cost, _)| cost)
+    }
 
-            if p.0 < 0 || p.0 >= bounds.0 || p.1 < 0 || p.1 >= bounds.1 {
-                continue;
-            }
+    /// Steps to the exit once `params.bytes` bytes have fallen.
+    fn escape(input: &str, params: &Day18Params) -> Result<String, Error> {
+        let d = Day18::parse(input, params)?;
+        let corrupt: HashSet<P> = d.bytes.iter().take(params.bytes).copied().collect();
 
-            if obstacles.contains(&p) {
-                continue;
-            }
+        Day18::shortest(d.start, d.end, &corrupt, d.size)
+            .map(|cost| cost.to_string())
+            .ok_or_else(|| Error::no_answer(18, "the exit is already cut off"))
+    }
 
-            if p == e {
-                return cost;
-            }
+    /// The first byte that cuts the exit off, as `x,y`.
+    fn blocker(input: &str, params: &Day18Params) -> Result<String, Error> {
+        let d = Day18::parse(input, params)?;
+        let blocked = |n: usize| {
+            let corrupt: HashSet<P> = d.bytes[..n].iter().copied().collect();
+            Day18::shortest(d.start, d.end, &corrupt, d.size).is_none()
+        };
 
-            if seen.contains(&p) {
-                continue;
-            }
-            seen.insert(p);
+        if !blocked(d.bytes.len()) {
+            return Err(Error::no_answer(18, "the exit is never cut off"));
+        }
 
-            for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
-                q.push(QueueState((p.0 + dx, p.1 + dy), cost + 1));
+        // the fewest bytes that block the way
+        let (mut low, mut high) = (0, d.bytes.len());
+        while low < high {
+            let mid = (low + high) / 2;
+            if blocked(mid) {
+                high = mid;
+            } else {
+                low = mid + 1;
             }
         }
 
-        -1
+        let (x, y) = low
+            .checked_sub(1)
+            .map(|i| d.bytes[i])
+            .ok_or_else(|| Error::no_answer(18, "the exit is cut off before any byte falls"))?;
+        Ok(format!("{x},{y}"))
     }
 }
 
 impl Day<String> for Day18 {
-    fn part1(input: &str) -> String {
-        let d = Day18::parse(input, None);
-        let mut corrupt = HashSet::<P>::new();
-
-        for p in d.bytes.iter().take(1024) {
-            corrupt.insert(*p);
-        }
+    type Params = Day18Params;
 
-        format!("{}", Day18::dijkstra(d.start, d.end, &corrupt, d.size))
+    fn part1(input: &str) -> String {
+        Self::try_part1_with(input, &Day18Params::default()).unwrap_or_else(|e| panic!("{e}"))
     }
 
     fn part2(input: &str) -> String {
-        let d = Day18::parse(input, None);
-        let mut corrupt = HashSet::<P>::new();
+        Self::try_part2_with(input, &Day18Params::default()).unwrap_or_else(|e| panic!("{e}"))
+    }
 
-        // let mut fatal_byte: Option<P> = None;
-        // for p in d.bytes.iter() {
-        //     corrupt.insert(*p);
-        //     let res = Day18::dijkstra(d.start, d.end, &corrupt, d.size);
-        //     if res == -1 {
-        //         fatal_byte = Some(*p);
-        //         break;
-        //     }
-        // }
+    fn try_part1(input: &str) -> Result<String, Error> {
+        Self::try_part1_with(input, &Day18Params::default())
+    }
 
-        let mut low = 0;
-        let mut high = d.bytes.len();
-        let fatal_byte;
-        loop {
-            let mid = (low + high) / 2;
-            corrupt.clear();
-            for i in 0..mid {
-                corrupt.insert(d.bytes[i]);
-            }
-            let res = Day18::dijkstra(d.start, d.end, &corrupt, d.size);
-            if res == -1 {
-                // fatal_byte = Some(d.bytes[mid]);
-                high = mid;
-            } else {
-                low = mid + 1;
-            }
+    fn try_part2(input: &str) -> Result<String, Error> {
+        Self::try_part2_with(input, &Day18Params::default())
+    }
 
-            if low >= high {
-                fatal_byte = Some(d.bytes[mid]);
-                break;
-            }
-        }
+    fn validate(input: &str) -> Option<Result<(), Error>> {
+        Some(Day18::parse(input, &Day18Params::default()).map(|_| ()))
+    }
 
-        if let Some((x, y)) = fatal_byte {
-            format!("{x},{y}")
-        } else {
-            "-".to_string()
-        }
+    fn try_part1_with(input: &str, params: &Day18Params) -> Result<String, Error> {
+        Day18::escape(input, params)
+    }
+
+    fn try_part2_with(input: &str, params: &Day18Params) -> Result<String, Error> {
+        Day18::blocker(input, params)
     }
 }
 
@@ -173,34 +161,38 @@ mod test {
         2,0
     ";
 
-    const TEST_SIZE: P = (7, 7);
+    const TEST_PARAMS: Day18Params = Day18Params {
+        width: 7,
+        height: 7,
+        bytes: 12,
+    };
 
     #[test]
     fn p1_t() {
-        let d = Day18::parse(TEST_INPUT, Some(TEST_SIZE));
+        let d = Day18::parse(TEST_INPUT, &TEST_PARAMS).unwrap();
         let mut corrupt = HashSet::<P>::new();
 
         for p in d.bytes.iter().take(12) {
             corrupt.insert(*p);
         }
 
-        assert_eq!(
-            22,
-            Day18::dijkstra((0, 0), (6, 6), &corrupt, d.size) as isize
-        );
+        assert_eq!(Some(22), Day18::shortest((0, 0), (6, 6), &corrupt, d.size));
+
+        corrupt.extend(d.bytes[12..21].iter().copied());
+        assert_eq!(None, Day18::shortest((0, 0), (6, 6), &corrupt, d.size));
     }
 
     #[test]
     fn p2_t() {
-        let d = Day18::parse(TEST_INPUT, Some(TEST_SIZE));
+        let d = Day18::parse(TEST_INPUT, &TEST_PARAMS).unwrap();
         let mut corrupt = HashSet::<P>::new();
 
         // naive solution is linear search
 
         // for p in d.bytes.iter() {
         //     corrupt.insert(*p);
-        //     let res = Day18::dijkstra((0, 0), (6, 6), &corrupt, d.size);
-        //     if res == -1 {
+        //     let res = Day18::shortest((0, 0), (6, 6), &corrupt, d.size);
+        //     if res.is_none() {
         //         fatal_byte = Some(*p);
         //         break;
         //     }
@@ -217,8 +209,8 @@ mod test {
             for i in 0..mid {
                 corrupt.insert(d.bytes[i]);
             }
-            let res = Day18::dijkstra(d.start, d.end, &corrupt, d.size);
-            if res == -1 {
+            let res = Day18::shortest(d.start, d.end, &corrupt, d.size);
+            if res.is_none() {
                 // fatal_byte = Some(d.bytes[mid]);
                 high = mid;
             } else {
@@ -233,4 +225,52 @@ mod test {
 
         assert_eq!(Some((6, 1)), fatal_byte);
     }
+
+    #[test]
+    fn with_params() {
+        assert_eq!(
+            Ok("22".to_string()),
+            Day18::try_part1_with(TEST_INPUT, &TEST_PARAMS)
+        );
+        assert_eq!(
+            Ok("6,1".to_string()),
+            Day18::try_part2_with(TEST_INPUT, &TEST_PARAMS)
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        let bad = TEST_INPUT.replace("6,3", "6;3");
+        assert!(matches!(
+            Day18::validate(&bad),
+            Some(Err(Error::Parse {
+                line: 7,
+                column: 9,
+                ..
+            }))
+        ));
+        assert!(Day18::try_part1_with(&bad, &TEST_PARAMS).is_err());
+        assert_eq!(Some(Ok(())), Day18::validate(TEST_INPUT));
+    }
+
+    #[test]
+    fn no_way_out() {
+        let blocked = Day18Params {
+            bytes: 21,
+            ..TEST_PARAMS
+        };
+        assert_eq!(
+            Err(Error::no_answer(18, "the exit is already cut off")),
+            Day18::try_part1_with(TEST_INPUT, &blocked)
+        );
+
+        let few: String = parse::lines(TEST_INPUT)
+            .take(12)
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert_eq!(
+            Err(Error::no_answer(18, "the exit is never cut off")),
+            Day18::try_part2_with(&few, &TEST_PARAMS)
+        );
+    }
 }
@@ -74,6 +74,8 @@ impl Day19 {
 }
 
 impl Day<usize> for Day19 {
+    type Params = ();
+
     fn part1(input: &str) -> usize {
         let d = Day19::parse(input);
         d.designs
@@ -1,6 +1,4 @@
-use std::collections::{BinaryHeap, HashMap};
-
-use crate::{intmap::IntMap, Day};
+use crate::{distance_field, intmap::IntMap, params, Day, Error};
 
 #[derive(PartialEq, Eq, Default, Clone, Copy, Debug)]
 enum O {
@@ -17,21 +15,6 @@ fn manhattan(a: &Co, b: &Co) -> isize {
     (a.0 - b.0).abs() + (a.1 - b.1).abs()
 }
 
-#[derive(Eq, PartialEq, Clone)]
-struct QNode(i32, Co, Vec<(Co, i32)>);
-
-impl Ord for QNode {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        other.0.cmp(&self.0)
-    }
-}
-
-impl PartialOrd for QNode {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
 pub struct Day20 {
     map: IntMap<O>,
     s: (isize, isize),
@@ -70,98 +53,107 @@ impl Day20 {
         }
     }
 
-    fn dijkstra(
-        m: &IntMap<O>,
-        start: &Co,
-        end: &Co,
-    ) -> (Option<i32>, IntMap<bool>, Vec<(Co, i32)>) {
-        let mut seen = IntMap::<bool>::new(m.width, m.height);
-        let mut q = BinaryHeap::<QNode>::new();
-        // could replace route by storing cost in the 'seen' intmap
-        q.push(QNode(0, *start, vec![(*start, 0)]));
-
-        while let Some(QNode(cost, pos, route)) = q.pop() {
-            if &pos == end {
-                return (Some(cost), seen, route);
-            }
+    /// Steps from the start and from the end to every tile of the track.
+    fn fields(&self) -> (IntMap<Option<u32>>, IntMap<Option<u32>>) {
+        let track = |o: &O| *o != O::Wall;
+        (
+            distance_field(&self.map, self.s, track),
+            distance_field(&self.map, self.e, track),
+        )
+    }
 
-            if let Some(true) = seen.get(pos) {
+    /// Every cheat of up to `length` steps that saves time, as
+    /// `(start, end, saving)`. Works on any track layout, not just a
+    /// single corridor: a cheat is scored against the best honest route.
+    fn cheats(&self, length: u32) -> Vec<(Co, Co, u32)> {
+        let (from_s, from_e) = self.fields();
+        let Some(best) = from_s[self.e] else {
+            return vec![];
+        };
+
+        let reach = length as isize;
+        let mut out = vec![];
+        for (start, ds) in from_s.iter() {
+            let Some(ds) = *ds else {
                 continue;
-            } else {
-                seen.set(pos, true);
-            }
-
-            for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
-                let co = (pos.0 + dx, pos.1 + dy);
-                match m.get(co) {
-                    None => {}
-                    Some(O::Wall) => {}
-                    _ => {
-                        let mut new_route = route.clone();
-                        new_route.push((co, cost + 1));
-                        q.push(QNode(cost + 1, co, new_route));
+            };
+            for dy in -reach..=reach {
+                let span = reach - dy.abs();
+                for dx in -span..=span {
+                    let end = (start.0 + dx, start.1 + dy);
+                    let Some(&Some(de)) = from_e.get(end) else {
+                        continue;
+                    };
+                    let cost = ds + manhattan(&start, &end) as u32 + de;
+                    if cost < best {
+                        out.push((start, end, best - cost));
                     }
                 }
             }
         }
 
-        (None, seen, vec![])
+        out
     }
 
-    fn find_cheats(input: &str, length: i32) -> Vec<(i32, i32)> {
-        let m = Day20::parse(input);
-        let (_, _, route) = &Day20::dijkstra(&m.map, &m.s, &m.e);
-
-        // let mut cheats = HashMap::<(Co, Co), i32>::new();
-        let mut cheats = HashMap::<i32, i32>::new();
-        for (idx, &(start, start_cost)) in route.iter().enumerate() {
-            for &(end, end_cost) in route.iter().skip(idx + length as usize) {
-                let man_dist = manhattan(&start, &end) as i32;
-                let route_diff = end_cost - start_cost;
-                if man_dist <= length && man_dist < route_diff {
-                    cheats
-                        .entry(route_diff - man_dist)
-                        .and_modify(|v| *v += 1)
-                        .or_insert(1);
-                }
-            }
-        }
-
-        let mut vec_res: Vec<_> = cheats.into_iter().map(|(k, v)| (v, k)).collect();
-        vec_res.sort_by_key(|v| v.1);
+    /// How many cheats of up to `length` steps save at least `min_saving`.
+    fn count(input: &str, length: u32, min_saving: u32) -> i64 {
+        let d = Day20::parse(input);
+        d.cheats(length)
+            .iter()
+            .filter(|c| c.2 >= min_saving)
+            .count() as i64
+    }
+}
 
-        vec_res
+params! {
+    pub struct Day20Params {
+        /// the least time a cheat must save to be counted
+        pub min_saving: u32 = 100,
     }
 }
 
 impl Day<i64> for Day20 {
-    fn part1(input: &str) -> i64 {
-        let results = Day20::find_cheats(input, 2);
+    type Params = Day20Params;
 
-        results
-            .iter()
-            .filter(|&(_count, cost)| *cost >= 100)
-            .map(|(count, _)| *count as i64)
-            .sum::<i64>()
+    fn part1(input: &str) -> i64 {
+        Day20::count(input, 2, Day20Params::default().min_saving)
     }
 
     fn part2(input: &str) -> i64 {
-        let results = Day20::find_cheats(input, 20);
+        Day20::count(input, 20, Day20Params::default().min_saving)
+    }
 
-        results
-            .iter()
-            .filter(|&(_count, cost)| *cost >= 100)
-            .map(|(count, _)| *count as i64)
-            .sum::<i64>()
+    fn try_part1_with(input: &str, params: &Day20Params) -> Result<i64, Error> {
+        Ok(Day20::count(input, 2, params.min_saving))
+    }
+
+    fn try_part2_with(input: &str, params: &Day20Params) -> Result<i64, Error> {
+        Ok(Day20::count(input, 20, params.min_saving))
     }
 }
 
 #[cfg(test)]
 mod test {
 
+    use std::collections::HashMap;
+
     use super::*;
 
-    const TEST_INPUT: &'static str = "
+    /// How many cheats save each amount of time, as `(count, saving)`
+    /// sorted by saving.
+    fn find_cheats(input: &str, length: u32) -> Vec<(i32, u32)> {
+        let mut counts = HashMap::<u32, i32>::new();
+        for (_, _, saving) in Day20::parse(input).cheats(length) {
+            *counts.entry(saving).or_default() += 1;
+        }
+
+        let mut vec_res: Vec<_> = counts.into_iter().map(|(k, v)| (v, k)).collect();
+        vec_res.sort_by_key(|v| v.1);
+
+        vec_res
+    }
+
+    const TEST_INPUT: &str = "
         ###############
         #...#...#.....#
         #.#.#.#.#.###.#
@@ -195,7 +187,42 @@ mod test {
                 (1, 40),
                 (1, 64)
             ],
-            Day20::find_cheats(TEST_INPUT, 2)
+            find_cheats(TEST_INPUT, 2)
         )
     }
+
+    #[test]
+    fn p2_t() {
+        let cheats = Day20::parse(TEST_INPUT).cheats(20);
+        assert_eq!(285, cheats.iter().filter(|c| c.2 >= 50).count());
+        assert_eq!(3, cheats.iter().filter(|c| c.2 == 76).count());
+
+        let params = Day20Params { min_saving: 50 };
+        assert_eq!(Ok(285), Day20::try_part2_with(TEST_INPUT, &params));
+    }
+
+    #[test]
+    fn branching_track() {
+        // the dead end below S is never on the honest route, but a cheat
+        // from its tip through to E is the best one going
+        let d = Day20::parse(
+            "
+            ########
+            #S....##
+            #.###.##
+            #.#E..##
+            ########
+        ",
+        );
+
+        let (from_s, from_e) = d.fields();
+        assert_eq!(Some(8), from_s[d.e]);
+        assert_eq!(Some(2), from_s[(1, 3)]);
+        assert_eq!(Some(8), from_e[(1, 1)]);
+        assert_eq!(None, from_e[(2, 2)]);
+
+        let cheats = d.cheats(2);
+        assert!(cheats.contains(&((1, 3), (3, 3), 4)));
+        assert_eq!(4, cheats.iter().map(|c| c.2).max().unwrap());
+    }
 }
@@ -21,9 +21,17 @@ Find the fewest number of button presses you'll need to perform in order to caus
 
 use std::collections::HashMap;
 
-use crate::Day;
+use crate::{params, Day, Error};
 pub struct Day21 {}
 
+params! {
+    pub struct Day21Params {
+        /// Robots using directional keypads in the chain.
+        pub robots1: usize = 2,
+        pub robots2: usize = 25,
+    }
+}
+
 // we can just treat x then y deltas as movement instructions
 // so e.g. (0,0) -> (3,1) = >>>v
 
@@ -314,48 +322,54 @@ fn plan_rec(
 //     out.len()
 // }
 
-impl Day<isize> for Day21 {
-    fn part1(input: &str) -> isize {
-        let n = parse_map(NUMPAD);
-        let a = parse_map(ARROWS);
-
-        let mut complexity = 0;
-        for p0 in input.split_whitespace() {
-            let p1 = plan(p0.as_bytes(), &n);
-            let mut cache = HashMap::new();
-            let p3 = plan_rec(&p1, &n, &a, 1, 1, &mut cache);
-            // let p3 = plan(&p2, &a);
-
-            complexity += p0[0..3].parse::<isize>().unwrap() * p3 as isize;
-        }
+/// Sum of code complexities with `robots` directional keypads between us
+/// and the numeric keypad. With none, ours drives the numeric keypad's
+/// robot directly.
+fn complexity(input: &str, robots: usize) -> isize {
+    let n = parse_map(NUMPAD);
+    let a = parse_map(ARROWS);
+
+    let mut complexity = 0;
+    for p0 in input.split_whitespace() {
+        let p1 = plan(p0.as_bytes(), &n);
+
+        // let mut curr = p1;
+        // for _i in 0..25 {
+        //     curr = plan(&curr, &a);
+        //     // println!("{_i}: {:?}", &curr.len());
+        //     println!("{_i}");
+        // }
+
+        // let p3 = plan(&curr, &a);
+
+        let p3_len = match robots.checked_sub(1) {
+            None => p1.len(),
+            Some(depth) => plan_rec(&p1, &n, &a, depth, depth, &mut HashMap::new()),
+        };
 
-        complexity
+        complexity += p0[0..3].parse::<isize>().unwrap() * p3_len as isize;
     }
 
-    fn part2(input: &str) -> isize {
-        let n = parse_map(NUMPAD);
-        let a = parse_map(ARROWS);
-
-        let mut complexity = 0;
-        for p0 in input.split_whitespace() {
-            let p1 = plan(p0.as_bytes(), &n);
+    complexity
+}
 
-            // let mut curr = p1;
-            // for _i in 0..25 {
-            //     curr = plan(&curr, &a);
-            //     // println!("{_i}: {:?}", &curr.len());
-            //     println!("{_i}");
-            // }
+impl Day<isize> for Day21 {
+    type Params = Day21Params;
 
-            // let p3 = plan(&curr, &a);
+    fn part1(input: &str) -> isize {
+        complexity(input, Day21Params::default().robots1)
+    }
 
-            let mut cache = HashMap::new();
-            let p3_len = plan_rec(&p1, &n, &a, 24, 24, &mut cache);
+    fn part2(input: &str) -> isize {
+        complexity(input, Day21Params::default().robots2)
+    }
 
-            complexity += p0[0..3].parse::<isize>().unwrap() * p3_len as isize;
-        }
+    fn try_part1_with(input: &str, params: &Day21Params) -> Result<isize, Error> {
+        Ok(complexity(input, params.robots1))
+    }
 
-        complexity
+    fn try_part2_with(input: &str, params: &Day21Params) -> Result<isize, Error> {
+        Ok(complexity(input, params.robots2))
     }
 }
 
@@ -439,6 +453,15 @@ mod test {
         }
     }
 
+    #[test]
+    fn robot_counts() {
+        // "029A" takes 12 presses on the keypad driving the numeric one, and
+        // 28 and 68 with one and two robots in between
+        assert_eq!(12 * 29, complexity("029A", 0));
+        assert_eq!(28 * 29, complexity("029A", 1));
+        assert_eq!(68 * 29, complexity("029A", 2));
+    }
+
     #[test]
     fn solve_p1() {
         assert_eq!(
@@ -1,8 +1,15 @@
 use std::collections::HashMap;
 
-use crate::Day;
+use crate::{params, Day, Error};
 pub struct Day22 {}
 
+params! {
+    pub struct Day22Params {
+        /// New secret numbers each buyer generates.
+        pub rounds: usize = 2000,
+    }
+}
+
 /*
 
 --- Part Two ---
@@ -97,13 +104,33 @@ fn next(secret: i64) -> i64 {
 }
 
 impl Day<isize> for Day22 {
+    type Params = Day22Params;
+
     fn part1(input: &str) -> isize {
+        Day22::secrets(input, &Day22Params::default())
+    }
+
+    fn part2(input: &str) -> isize {
+        Day22::bananas(input, &Day22Params::default())
+    }
+
+    fn try_part1_with(input: &str, params: &Day22Params) -> Result<isize, Error> {
+        Ok(Day22::secrets(input, params))
+    }
+
+    fn try_part2_with(input: &str, params: &Day22Params) -> Result<isize, Error> {
+        Ok(Day22::bananas(input, params))
+    }
+}
+
+impl Day22 {
+    fn secrets(input: &str, params: &Day22Params) -> isize {
         let inits = input.split_whitespace().map(|v| v.parse::<i64>().unwrap());
 
         inits
             .map(|n| {
                 let mut curr = n as i64;
-                for _ in 0..2000 {
+                for _ in 0..params.rounds {
                     curr = next(curr);
                 }
 
@@ -112,14 +139,14 @@ impl Day<isize> for Day22 {
             .sum::<i64>() as isize
     }
 
-    fn part2(input: &str) -> isize {
+    fn bananas(input: &str, params: &Day22Params) -> isize {
         let inits = input.split_whitespace().map(|v| v.parse::<i64>().unwrap());
 
         let number_sets: Vec<Vec<_>> = inits
             .map(|n| {
                 let mut curr = n as i64;
                 let mut v = vec![n];
-                for _ in 0..2000 {
+                for _ in 0..params.rounds {
                     curr = next(curr);
                     v.push(curr);
                 }
@@ -188,6 +188,8 @@ impl Day23 {
 }
 
 impl Day<String> for Day23 {
+    type Params = ();
+
     fn part1(input: &str) -> String {
         let d = Day23::parse(input);
         let triples = Day23::find_threes(&d, 3);
@@ -1,13 +1,16 @@
-use std::{
-    collections::{HashMap, HashSet},
-    fmt::Write,
-};
+use std::collections::{BTreeSet, HashMap};
 
-use crate::Day;
+use crate::{
+    intmap::IntMap,
+    params, parse, render,
+    render::{Style, GREEN, RED},
+    Day, Error,
+};
 use regex::Regex;
 
 fn func_re() -> Regex {
-    Regex::new(r"(?<left>[\w\d]+) (?<op>XOR|OR|AND) (?<right>[\w\d]+) -> (?<out>[\w\d]+)").unwrap()
+    Regex::new(r"^(?<left>[\w\d]+) (?<op>XOR|OR|AND) (?<right>[\w\d]+) -> (?<out>[\w\d]+)$")
+        .unwrap()
 }
 
 type Name = String;
@@ -28,45 +31,67 @@ impl std::fmt::Display for Node {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Node::Const(v) => f.write_str(if *v { "T" } else { "F" }),
-            Node::XOR(l, r) => {
+            Node::XOR(..) => {
                 write!(f, "^",)
             }
-            Node::OR(l, r) => {
+            Node::OR(..) => {
                 write!(f, "|")
             }
-            Node::AND(l, r) => {
+            Node::AND(..) => {
                 write!(f, "&")
             }
         }
     }
 }
 
+params! {
+    pub struct Day24Params {
+        /// Pairs of gates whose outputs were swapped.
+        pub pairs: usize = 4,
+    }
+}
+
 pub struct Day24 {
     table: HashMap<Name, Node>,
     zmax: usize,
 }
 
 impl Day24 {
-    fn parse(input: &str) -> Self {
-        let (inputs, connects) = input.split_once("\n\n").unwrap();
+    fn parse(input: &str) -> Result<Self, Error> {
+        let [inputs, connects, ..] = parse::sections(input)[..] else {
+            return Err(Error::truncated(
+                24,
+                "a blank line between inputs and gates",
+            ));
+        };
 
         let mut table = HashMap::<Name, Node>::new();
 
-        for line in inputs.trim().lines() {
-            let (left, right) = line.trim().split_once(": ").unwrap();
-            table.insert(
-                left.to_string(),
-                Node::Const(if right == "1" { true } else { false }),
-            );
+        for line in parse::lines(inputs) {
+            let (left, right) = line
+                .split_once(": ")
+                .ok_or_else(|| Error::parse(24, input, line, "'<wire>: <0|1>'"))?;
+            let value = match right {
+                "0" => false,
+                "1" => true,
+                _ => return Err(Error::parse(24, input, right, "0 or 1")),
+            };
+            table.insert(left.to_string(), Node::Const(value));
         }
 
         let pat = func_re();
         let mut zmax = 0;
-        for line in connects.trim().lines() {
-            // println!("|{line}|");
-            let captures = pat.captures(line).unwrap();
-            let left = captures["left"].to_string();
-            let right = captures["right"].to_string();
+        let mut args = vec![];
+        for line in parse::lines(connects) {
+            let captures = pat
+                .captures(line)
+                .ok_or_else(|| Error::parse(24, input, line, "'<wire> <op> <wire> -> <wire>'"))?;
+            let (left, right) = (
+                captures.name("left").unwrap(),
+                captures.name("right").unwrap(),
+            );
+            args.extend([left.as_str(), right.as_str()]);
+            let (left, right) = (left.as_str().to_string(), right.as_str().to_string());
             let node = match &captures["op"] {
                 "XOR" => Node::XOR(left, right),
                 "OR" => Node::OR(left, right),
@@ -76,14 +101,36 @@ impl Day24 {
 
             let out = captures["out"].to_string();
 
-            if out.starts_with("z") {
-                let num: usize = out[1..].parse().unwrap();
+            if let Some(digits) = out.strip_prefix('z') {
+                let num: usize = digits.parse().map_err(|_| {
+                    Error::parse(
+                        24,
+                        input,
+                        &line[line.len() - out.len()..],
+                        "a numbered z wire",
+                    )
+                })?;
                 zmax = zmax.max(num);
             }
             table.insert(out, node);
         }
 
-        Day24 { table, zmax }
+        if let Some(arg) = args.into_iter().find(|w| !table.contains_key(*w)) {
+            return Err(Error::parse(
+                24,
+                input,
+                arg,
+                "a wire with a value or a gate",
+            ));
+        }
+        if (0..=zmax).any(|k| !table.contains_key(&format!("z{k:02}"))) {
+            return Err(Error::truncated(
+                24,
+                "a gate for every z wire up to the highest",
+            ));
+        }
+
+        Ok(Day24 { table, zmax })
     }
 
     fn combine(n: Node, l: bool, r: bool) -> bool {
@@ -131,141 +178,267 @@ impl Day24 {
         res
     }
 
-    fn evaluate_trace(&self) -> (u64, Vec<Vec<(Name, Node)>>) {
-        let mut res = 0;
-        let mut traces = vec![];
-        for digit in 0..=self.zmax {
-            let name = format!("z{:02}", self.zmax - digit);
-            let (digit, trace) = self.eval(&name);
-            traces.push(trace);
-            res <<= 1;
-            res |= if digit { 1 } else { 0 };
+    /// Gate outputs that break the wiring of a ripple-carry adder: every
+    /// z but the last comes from an XOR of a half-sum and a carry, each
+    /// x/y XOR feeds that XOR, each AND feeds the OR that makes the next
+    /// carry, and each carry feeds the next bit's XOR and AND. Bit 0 has
+    /// no carry in, so its gates are exempt.
+    fn suspects(&self) -> BTreeSet<&str> {
+        let is_input = |w: &str| matches!(first(w), 'x' | 'y');
+        let last_z = format!("z{:02}", self.zmax);
+
+        let mut consumers = HashMap::<&str, Vec<&Node>>::new();
+        for node in self.table.values() {
+            if let Node::XOR(l, r) | Node::AND(l, r) | Node::OR(l, r) = node {
+                consumers.entry(l).or_default().push(node);
+                consumers.entry(r).or_default().push(node);
+            }
+        }
+        let feeds = |w: &str, op: fn(&Node) -> bool| {
+            consumers.get(w).is_some_and(|ns| ns.iter().any(|n| op(n)))
+        };
+        let is_xor = |n: &Node| matches!(n, Node::XOR(..));
+        let is_and = |n: &Node| matches!(n, Node::AND(..));
+        let is_or = |n: &Node| matches!(n, Node::OR(..));
+
+        let mut bad = BTreeSet::new();
+        for (out, node) in &self.table {
+            let (l, r) = match node {
+                Node::Const(_) => continue,
+                Node::XOR(l, r) | Node::AND(l, r) | Node::OR(l, r) => (l, r),
+            };
+            let from_inputs = is_input(l) && is_input(r);
+            let first_bit = from_inputs && l.ends_with("00") && r.ends_with("00");
+            let is_z = first(out) == 'z';
+
+            let wrong = match node {
+                _ if *out == last_z => self.zmax > 1 && !is_or(node),
+                _ if is_z && !is_xor(node) => true,
+                Node::XOR(..) if first_bit => out != "z00",
+                Node::XOR(..) if from_inputs => is_z || !feeds(out, is_xor),
+                Node::XOR(..) => !is_z,
+                Node::AND(..) => !first_bit && !feeds(out, is_or),
+                Node::OR(..) => !feeds(out, is_xor) || !feeds(out, is_and),
+                _ => false,
+            };
+            if wrong {
+                bad.insert(out.as_str());
+            }
+        }
+        bad
+    }
 
-            // digits.push(self.eval(&name).0);
+    /// For each z bit in turn, the gates it depends on that no lower bit
+    /// already does, starting from the z gate itself. In a sound adder
+    /// the middle bits all add the same five gates.
+    fn cones(&self) -> Vec<Vec<&str>> {
+        let mut seen = BTreeSet::new();
+        (0..=self.zmax)
+            .map(|k| {
+                let mut cone = vec![];
+                let mut todo = vec![format!("z{k:02}")];
+                while let Some(w) = todo.pop() {
+                    let Some((w, node)) = self.table.get_key_value(&w) else {
+                        continue;
+                    };
+                    if let Node::XOR(l, r) | Node::AND(l, r) | Node::OR(l, r) = node {
+                        if seen.insert(w.as_str()) {
+                            cone.push(w.as_str());
+                            todo.extend([r.clone(), l.clone()]);
+                        }
+                    }
+                }
+                cone
+            })
+            .collect()
+    }
+
+    /// The value on wire `w` with `x` and `y` on the inputs, or `None` if
+    /// it depends on itself.
+    fn value<'a>(
+        &'a self,
+        w: &'a str,
+        (x, y): (u64, u64),
+        swaps: &HashMap<&'a str, &'a str>,
+        memo: &mut HashMap<&'a str, Option<bool>>,
+    ) -> Option<bool> {
+        if let Some(&v) = memo.get(w) {
+            return v;
         }
+        memo.insert(w, None);
+
+        let gate = swaps.get(w).copied().unwrap_or(w);
+        let mut arg = |w| self.value(w, (x, y), swaps, memo);
+        let v = match self.table.get(gate)? {
+            Node::Const(c) => match first(w) {
+                'x' => x.checked_shr(w[1..].parse().ok()?).unwrap_or(0) & 1 == 1,
+                'y' => y.checked_shr(w[1..].parse().ok()?).unwrap_or(0) & 1 == 1,
+                _ => *c,
+            },
+            Node::XOR(l, r) => arg(l)? != arg(r)?,
+            Node::AND(l, r) => arg(l)? && arg(r)?,
+            Node::OR(l, r) => arg(l)? || arg(r)?,
+        };
+
+        memo.insert(w, Some(v));
+        Some(v)
+    }
 
-        // digits.reverse();
+    /// What the circuit outputs for `x + y` with each pair of gates in
+    /// `swaps` trading outputs, or `None` if that leaves a loop.
+    fn add(&self, x: u64, y: u64, swaps: &HashMap<&str, &str>) -> Option<u64> {
+        let mut memo = HashMap::new();
+        let mut z = 0;
+        for k in 0..=self.zmax {
+            let (name, _) = self.table.get_key_value(&format!("z{k:02}"))?;
+            let bit = self.value(name, (x, y), swaps, &mut memo)?;
+            z |= (bit as u64).checked_shl(k as u32).unwrap_or(0);
+        }
+        Some(z)
+    }
 
-        (res, traces)
+    /// Whether the circuit adds correctly with `swaps` made, judged on
+    /// each bit alone, a carry rippling up from each bit, and alternating
+    /// bit patterns. Only for circuits of at most 63 input bits, so the
+    /// sum fits in a `u64`.
+    fn adds(&self, swaps: &HashMap<&str, &str>) -> bool {
+        let bits = self.zmax as u32;
+        let mask = u64::MAX >> (64 - bits);
+        let alt = 0x5555_5555_5555_5555 & mask;
+
+        let mut cases = vec![(alt, alt), (alt, !alt & mask), (alt << 1 & mask, alt)];
+        for k in 0..bits {
+            let bit = 1 << k;
+            cases.extend([(bit, 0), (0, bit), (bit, bit), (mask, bit), (mask >> k, 1)]);
+        }
+
+        cases
+            .into_iter()
+            .all(|(x, y)| self.add(x, y, swaps) == Some(x + y))
     }
 
-    fn visualise(&self) -> Vec<String> {
-        let traces = Vec::<String>::new();
-        for digit in 0..=self.zmax {
-            let name = format!("z{:02}", self.zmax - digit);
-            let (digit, trace) = self.eval(&name);
+    /// Try every way of choosing `pairs` pairs of suspect outputs to swap,
+    /// and keep the first that makes the circuit add. Suspects left out
+    /// of the pairs are taken to be false alarms.
+    fn repair(&self, pairs: usize) -> Result<Vec<&str>, Error> {
+        fn choose<'a>(
+            d: &'a Day24,
+            rest: &[&'a str],
+            pairs: usize,
+            swaps: &mut HashMap<&'a str, &'a str>,
+        ) -> bool {
+            if pairs == 0 {
+                return d.adds(swaps);
+            }
+            if rest.len() < pairs * 2 {
+                return false;
+            }
+            let (&a, rest) = rest.split_first().expect("at least two suspects left");
+            for (i, &b) in rest.iter().enumerate() {
+                swaps.insert(a, b);
+                swaps.insert(b, a);
+                let others: Vec<_> = rest[..i].iter().chain(&rest[i + 1..]).copied().collect();
+                if choose(d, &others, pairs - 1, swaps) {
+                    return true;
+                }
+                swaps.remove(a);
+                swaps.remove(b);
+            }
+            // or `a` isn't swapped at all
+            choose(d, rest, pairs, swaps)
         }
 
-        traces
+        if !(1..64).contains(&self.zmax) {
+            return Err(Error::no_answer(
+                24,
+                "can only check adders of 1 to 63 bits",
+            ));
+        }
+        let suspects: Vec<_> = self.suspects().into_iter().collect();
+        let mut swaps = HashMap::new();
+        if choose(self, &suspects, pairs, &mut swaps) {
+            Ok(swaps
+                .into_keys()
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect())
+        } else {
+            Err(Error::no_answer(
+                24,
+                "no set of swaps makes the circuit add",
+            ))
+        }
     }
 }
 
 impl Day<String> for Day24 {
-    fn part1(input: &str) -> String {
-        let d = Day24::parse(input);
+    type Params = Day24Params;
 
-        let out = d.evaluate();
-
-        format!("{}", out)
+    fn part1(input: &str) -> String {
+        Self::try_part1(input).unwrap_or_else(|e| panic!("{e}"))
     }
 
     fn part2(input: &str) -> String {
-        let mut d = Day24::parse(input);
-
-        // let z14 = d.table.get("z14").unwrap().clone();
-        // let z22 = d.table.get("z22").unwrap().clone();
-        // let z31 = d.table.get("z31").unwrap().clone();
-        // let z35 = d.table.get("z35").unwrap().clone();
-        // d.table.insert("z14".to_owned(), z31);
-        // d.table.insert("z22".to_owned(), z14);
-        // d.table.insert("z31".to_owned(), z35);
-        // d.table.insert("z35".to_owned(), z22);
-
-        let xnames = (0..=44).map(|i| format!("x{i:02}")).collect::<Vec<_>>();
-        let ynames = (0..=44).map(|i| format!("y{i:02}")).collect::<Vec<_>>();
-
-        let mut good_gates = HashSet::<Name>::new();
-        let mut bad_gates = Vec::<HashSet<Name>>::new();
-
-        for z in 0..=44 {
-            for x in 0..=44 {
-                if x == z {
-                    d.table.insert(xnames[x].clone(), Node::Const(true));
-                } else {
-                    d.table.insert(xnames[x].clone(), Node::Const(false));
-                }
-            }
-
-            for y in 0..=44 {
-                if y == z {
-                    d.table.insert(ynames[y].clone(), Node::Const(true));
-                } else {
-                    d.table.insert(ynames[y].clone(), Node::Const(false));
-                }
-            }
+        Self::try_part2(input).unwrap_or_else(|e| panic!("{e}"))
+    }
 
-            let x = 1_u64 << z;
-            // let y = 1_u64 << z;
-            let y = 1_u64 << z;
-            let expected = x + y;
+    fn validate(input: &str) -> Option<Result<(), Error>> {
+        Some(Day24::parse(input).map(|_| ()))
+    }
 
-            let (d, trace) = d.evaluate_trace();
-            println!("{z}: {expected} / got {d} = {x} + {y} ");
+    fn try_part1(input: &str) -> Result<String, Error> {
+        let d = Day24::parse(input)?;
 
-            if expected == d {
-                good_gates.extend(trace[z].iter().map(|(n, _)| n.clone()));
-            } else {
-                let set = trace[z].iter().map(|(n, _)| n.clone()).collect();
-                bad_gates.push(set);
-            }
-        }
+        let out = d.evaluate();
 
-        // bad_gates = bad_gates.difference(&good_gates).cloned().collect();
+        Ok(format!("{}", out))
+    }
 
-        // println!("{:?}", bad_gates);
+    fn try_part2(input: &str) -> Result<String, Error> {
+        Self::try_part2_with(input, &Day24Params::default())
+    }
 
-        println!("found {} bad digits", bad_gates.len());
+    fn try_part2_with(input: &str, params: &Day24Params) -> Result<String, Error> {
+        let d = Day24::parse(input)?;
+        Ok(d.repair(params.pairs)?.join(","))
+    }
 
-        let mut all_intersect = HashSet::new();
+    /// A row per z bit with the gates it adds to the circuit, the ones
+    /// breaking the adder's wiring in red.
+    fn render(input: &str, _params: &Day24Params) -> Option<Result<IntMap<Style>, Error>> {
+        let draw = || {
+            let d = Day24::parse(input)?;
+            let suspects = d.suspects();
+            let cones = d.cones();
+
+            let label = 4;
+            let width = label + cones.iter().map(Vec::len).max().unwrap_or(0);
+            let mut cells = vec![];
+            for (y, cone) in cones.iter().enumerate() {
+                let name = format!("z{y:02}");
+                cells.extend(
+                    name.chars()
+                        .enumerate()
+                        .map(|(x, c)| (x, y, Style::plain(c))),
+                );
+                cells.extend(cone.iter().enumerate().map(|(i, &w)| {
+                    let colour = if suspects.contains(w) { RED } else { GREEN };
+                    let glyph = d.table[w].to_string().chars().next().unwrap_or('?');
+                    (label + i, y, Style::plain(glyph).fg(colour))
+                }));
+            }
+            let cells = cells
+                .into_iter()
+                .map(|(x, y, s)| ((x as isize, y as isize), s));
+            Ok(render::sparse(width, cones.len(), Style::plain(' '), cells))
+        };
 
-        for s in bad_gates {
-            all_intersect = all_intersect.intersection(&s).cloned().collect();
-        }
-        println!("all intersect: {:?}", all_intersect);
-
-        // for s1 in &bad_gates {
-        //     for s2 in &bad_gates {
-        //         println!("{:?}", s1.intersection(s2));
-        //     }
-        // }
-
-        "-".to_string()
-
-        /*
-
-            from visual inspections
-            Z10
-            Z14
-            Z31
-
-            from 1-bit test
-            13/14
-            30
-            34
-            35
-
-            14
-            22
-            31
-            35
-        */
+        Some(draw())
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::fetch_input;
-
     use super::*;
 
     const TEST_INPUT: &'static str = "
@@ -320,7 +493,7 @@ tnw OR pbm -> gnj
 
     #[test]
     fn parse_test() {
-        let d = Day24::parse(TEST_INPUT);
+        let d = Day24::parse(TEST_INPUT).unwrap();
 
         assert_eq!(d.zmax, 12);
         assert_eq!(
@@ -329,9 +502,57 @@ tnw OR pbm -> gnj
         );
     }
 
+    #[test]
+    fn parse_errors() {
+        let bad = TEST_INPUT.replace("y03: 1", "y03: 2");
+        assert!(matches!(
+            Day24::parse(&bad),
+            Err(Error::Parse {
+                line: 10,
+                column: 6,
+                ..
+            })
+        ));
+
+        let bad = TEST_INPUT.replace("x00 OR x03 -> fst", "x00 NOR x03 -> fst");
+        assert!(matches!(
+            Day24::parse(&bad),
+            Err(Error::Parse {
+                line: 16,
+                column: 1,
+                ..
+            })
+        ));
+
+        let bad = TEST_INPUT.replace("x00 OR x03 -> fst", "x00 OR x03 -> fst junk");
+        assert!(matches!(
+            Day24::parse(&bad),
+            Err(Error::Parse {
+                line: 16,
+                column: 1,
+                ..
+            })
+        ));
+
+        let bad = TEST_INPUT.replace("tnw OR fst -> frj", "tnw OR fsx -> frj");
+        assert_eq!(
+            "day 24, line 24, column 8: expected a wire with a value or a gate, found \"fsx\"",
+            Day24::try_part1(&bad).unwrap_err().to_string()
+        );
+
+        let bad = TEST_INPUT.replace("bfw XOR mjb -> z00", "bfw XOR mjb -> z13");
+        assert_eq!(
+            Some(Error::truncated(
+                24,
+                "a gate for every z wire up to the highest"
+            )),
+            Day24::parse(&bad).err()
+        );
+    }
+
     #[test]
     fn eval_test() {
-        let mut d = Day24::parse(&TEST_INPUT);
+        let d = Day24::parse(TEST_INPUT).unwrap();
 
         let out = d.evaluate();
 
@@ -341,20 +562,141 @@ tnw OR pbm -> gnj
         assert_eq!(2024, usize::from_str_radix(&outs, 2).unwrap());
     }
 
-    #[test]
-    fn vis() {
-        let mut d = Day24::parse(&fetch_input(24).unwrap());
-
-        let (out, traces) = d.evaluate_trace();
-
-        for (idx, t) in traces.iter().enumerate() {
-            println!(
-                "{idx} {}",
-                &t.iter()
-                    .map(|(a, b)| format!("{}", b.to_string()))
-                    .collect::<Vec<String>>()
-                    .join("")
-            )
+    /// A ripple-carry adder for `bits`-bit numbers, with the outputs of
+    /// each pair of wires in `swaps` exchanged.
+    fn adder(bits: usize, swaps: &[(&str, &str)]) -> String {
+        let mut out = String::new();
+        for k in 0..bits {
+            out += &format!("x{k:02}: 0\ny{k:02}: 0\n");
+        }
+        out += "\n";
+
+        let mut gates = vec![
+            "x00 XOR y00 -> z00".to_string(),
+            "x00 AND y00 -> c00".to_string(),
+        ];
+        for k in 1..bits {
+            let carry = if k == bits - 1 {
+                format!("z{bits:02}")
+            } else {
+                format!("c{k:02}")
+            };
+            gates.extend([
+                format!("x{k:02} XOR y{k:02} -> s{k:02}"),
+                format!("x{k:02} AND y{k:02} -> a{k:02}"),
+                format!("s{k:02} XOR c{:02} -> z{k:02}", k - 1),
+                format!("s{k:02} AND c{:02} -> t{k:02}", k - 1),
+                format!("t{k:02} OR a{k:02} -> {carry}"),
+            ]);
         }
+
+        for g in gates {
+            let (gate, wire) = g.split_once(" -> ").unwrap();
+            let wire = swaps
+                .iter()
+                .find_map(|&(l, r)| match wire {
+                    w if w == l => Some(r),
+                    w if w == r => Some(l),
+                    _ => None,
+                })
+                .unwrap_or(wire);
+            out += &format!("{gate} -> {wire}\n");
+        }
+        out
+    }
+
+    #[test]
+    fn repairs_adder() {
+        let d = Day24::parse(&adder(12, &[])).unwrap();
+        assert!(d.suspects().is_empty());
+        assert!(d.adds(&HashMap::new()));
+        let none = Day24Params { pairs: 0 };
+        assert_eq!(Ok("".into()), Day24::try_part2_with(&adder(12, &[]), &none));
+        assert!(matches!(
+            Day24::try_part2(&adder(12, &[])),
+            Err(Error::NoAnswer { day: 24, .. })
+        ));
+
+        // a z from an OR, a half-sum and carry swapped within a bit, a z
+        // from an AND, and a z from the carry's OR
+        let swaps = [
+            ("z03", "c03"),
+            ("s06", "a06"),
+            ("z09", "t09"),
+            ("z10", "c10"),
+        ];
+        let input = adder(12, &swaps);
+        let d = Day24::parse(&input).unwrap();
+        assert!(!d.adds(&HashMap::new()));
+        assert_eq!("a06,c03,c10,s06,t09,z03,z09,z10", Day24::part2(&input));
+
+        // a carry's OR swapped with an x/y AND, which only the rule for ORs
+        // catches on its side; and a stray AND feeding nothing, which is
+        // suspect but needn't be swapped
+        let swaps = [
+            ("c04", "a06"),
+            ("z03", "c03"),
+            ("s08", "a08"),
+            ("z09", "t09"),
+        ];
+        let input = adder(12, &swaps) + "x01 AND y02 -> q00\n";
+        let d = Day24::parse(&input).unwrap();
+        assert!(d.suspects().contains("a06"));
+        assert!(d.suspects().contains("q00"));
+        assert_eq!("a06,a08,c03,c04,s08,t09,z03,z09", Day24::part2(&input));
+
+        // the sum bit that reads carry c01 now drives it, so it's a loop
+        let looped = Day24::parse(&adder(4, &[("c01", "z02")])).unwrap();
+        assert_eq!(None, looped.add(4, 0, &HashMap::new()));
+        assert!(!looped.adds(&HashMap::new()));
+
+        let wide = Day24::parse(&adder(64, &[])).unwrap();
+        assert_eq!(
+            Err(Error::no_answer(
+                24,
+                "can only check adders of 1 to 63 bits"
+            )),
+            wide.repair(4)
+        );
+
+        // wires past bit 63 read as 0 rather than overflowing the shift
+        let wider = Day24::parse(&adder(66, &[])).unwrap();
+        assert_eq!(Some(3), wider.add(1, 2, &HashMap::new()));
+    }
+
+    #[test]
+    fn render_cones() {
+        let rows = |input: &str| match Day24::render(input, &Day24Params::default()) {
+            Some(Ok(pic)) => pic
+                .ascii()
+                .lines()
+                .map(|l| l.trim_end().to_string())
+                .collect::<Vec<_>>(),
+            other => panic!("{other:?}"),
+        };
+        assert_eq!(
+            vec!["z00 ^", "z01 ^^&", "z02 ^^|&&", "z03 |&&"],
+            rows(&adder(3, &[]))
+        );
+
+        // the half-sum and carry of bit 1 swapped: the AND now feeding z01
+        // and the XOR feeding the carry both show up red
+        let input = adder(3, &[("s01", "a01")]);
+        assert_eq!(
+            vec!["z00 ^", "z01 ^&&", "z02 ^^|&^", "z03 |&&"],
+            rows(&input)
+        );
+        let pic = Day24::render(&input, &Day24Params::default())
+            .unwrap()
+            .unwrap();
+        let colour = |x: isize, y: isize| pic.get((x, y)).and_then(|s| s.fg);
+        assert_eq!(Some(RED), colour(5, 1));
+        assert_eq!(Some(RED), colour(8, 2));
+        assert_eq!(Some(GREEN), colour(4, 1));
+
+        assert!(matches!(
+            Day24::render("x00: 1", &Day24Params::default()),
+            Some(Err(Error::Truncated { day: 24, .. }))
+        ));
     }
 }
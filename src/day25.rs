@@ -1,20 +1,16 @@
-use crate::Day;
+use crate::{parse, Day};
 
 fn parse(input: &str) -> Day25 {
-    // split on blank lines
-    let sections = input.trim().split("\n\n");
+    let sections = parse::sections(input);
 
     let mut locks: Vec<[u8; 5]> = vec![];
     let mut keys: Vec<[u8; 5]> = vec![];
 
     for mech in sections {
-        let mech = mech.trim();
-
         let is_lock = mech.starts_with("#####");
 
         let mut heights: [u8; 5] = [0; 5];
-        for (height, line) in mech.lines().skip(1).take(5).enumerate() {
-            let line = line.trim();
+        for (height, line) in parse::lines(mech).skip(1).take(5).enumerate() {
             // println!("{}", line);
             for pos in 0..5 {
                 if line.as_bytes()[pos] == b'.' {
@@ -57,6 +53,8 @@ pub struct Day25 {
 }
 
 impl Day<isize> for Day25 {
+    type Params = ();
+
     fn part1(input: &str) -> isize {
         let d = parse(input);
 
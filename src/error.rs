@@ -0,0 +1,156 @@
+use std::fmt::Display;
+
+/// Something wrong with a puzzle input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// A token that doesn't fit what the parser expected at that point.
+    /// `line` and `column` are 1-based, counted in chars, or both 0 if the
+    /// position isn't known.
+    Parse {
+        day: u8,
+        line: usize,
+        column: usize,
+        token: String,
+        expected: &'static str,
+    },
+    /// The input ran out before the parser found something it needed.
+    Truncated { day: u8, expected: &'static str },
+    /// The input is well formed, but the search for an answer gave up.
+    NoAnswer { day: u8, reason: String },
+    /// Something the input describes went wrong when carried out.
+    Runtime { day: u8, message: String },
+}
+
+impl Error {
+    /// A parse error for `token`, which should be a subslice of `input`;
+    /// the position is worked out from where it sits in the original
+    /// string. A token from anywhere else is a bug in the caller, which
+    /// only debug builds panic on; release builds leave the position out.
+    pub fn parse(day: u8, input: &str, token: &str, expected: &'static str) -> Error {
+        let offset = (token.as_ptr() as usize).wrapping_sub(input.as_ptr() as usize);
+        let inside = offset <= input.len() && input.is_char_boundary(offset);
+        debug_assert!(inside, "token must be a slice of the input");
+        if !inside {
+            return Error::Parse {
+                day,
+                line: 0,
+                column: 0,
+                token: token.to_string(),
+                expected,
+            };
+        }
+
+        Error::at(day, input, offset, token, expected)
+    }
+
+    /// A parse error for `token` found at byte `offset` in `input`.
+    pub fn at(day: u8, input: &str, offset: usize, token: &str, expected: &'static str) -> Error {
+        let (line, column) = locate(input, offset);
+        Error::Parse {
+            day,
+            line,
+            column,
+            token: token.to_string(),
+            expected,
+        }
+    }
+
+    pub fn truncated(day: u8, expected: &'static str) -> Error {
+        Error::Truncated { day, expected }
+    }
+
+    pub fn no_answer(day: u8, reason: impl Into<String>) -> Error {
+        Error::NoAnswer {
+            day,
+            reason: reason.into(),
+        }
+    }
+
+    pub fn runtime(day: u8, message: impl Into<String>) -> Error {
+        Error::Runtime {
+            day,
+            message: message.into(),
+        }
+    }
+}
+
+fn locate(input: &str, offset: usize) -> (usize, usize) {
+    let before = &input[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let column = before[line_start..].chars().count() + 1;
+
+    (line, column)
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Parse {
+                day,
+                line: 0,
+                token,
+                expected,
+                ..
+            } => write!(f, "day {day}: expected {expected}, found {token:?}"),
+            Error::Parse {
+                day,
+                line,
+                column,
+                token,
+                expected,
+            } => write!(
+                f,
+                "day {day}, line {line}, column {column}: expected {expected}, found {token:?}"
+            ),
+            Error::Truncated { day, expected } => {
+                write!(f, "day {day}: input ended, expected {expected}")
+            }
+            Error::NoAnswer { day, reason } => write!(f, "day {day}: no answer, {reason}"),
+            Error::Runtime { day, message } => write!(f, "day {day}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn position_of_token() {
+        let input = "12 34\n56 x7\n";
+        let token = input.split_whitespace().nth(3).unwrap();
+
+        assert_eq!(
+            Error::parse(1, input, token, "a number"),
+            Error::Parse {
+                day: 1,
+                line: 2,
+                column: 4,
+                token: "x7".to_string(),
+                expected: "a number",
+            }
+        );
+    }
+
+    #[test]
+    #[cfg_attr(debug_assertions, should_panic = "token must be a slice of the input")]
+    fn token_from_elsewhere() {
+        let token = String::from("x7");
+        assert_eq!(
+            "day 1: expected a number, found \"x7\"",
+            Error::parse(1, "12 34\n56 x7\n", &token, "a number").to_string()
+        );
+    }
+
+    #[test]
+    fn display() {
+        let e = Error::at(15, "#.\n#?", 4, "?", "a map tile");
+        assert_eq!(
+            "day 15, line 2, column 2: expected a map tile, found \"?\"",
+            e.to_string()
+        );
+    }
+}
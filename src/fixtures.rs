@@ -0,0 +1,138 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Context};
+
+use crate::Part;
+
+/// A worked example from a puzzle description, stored on disk as
+/// `examples/dayNN/<name>.txt` with its expected answers (and any
+/// parameters the example needs) in `examples/dayNN/<name>.expected`.
+///
+/// The `.expected` file is `key = value` lines; `part1` and `part2` are
+/// the answers, everything else is a parameter. `#` starts a comment.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Fixture {
+    pub day: u8,
+    pub name: String,
+    pub input: String,
+    pub part1: Option<String>,
+    pub part2: Option<String>,
+    pub params: BTreeMap<String, String>,
+}
+
+impl Fixture {
+    /// The `examples/` directory in this checkout.
+    pub fn default_dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("examples")
+    }
+
+    pub fn expected(&self, part: Part) -> Option<&str> {
+        match part {
+            Part::One => self.part1.as_deref(),
+            Part::Two => self.part2.as_deref(),
+        }
+    }
+
+    /// Every fixture for `day`, sorted by name. A day with no directory
+    /// just has no fixtures.
+    pub fn load(dir: &Path, day: u8) -> Result<Vec<Fixture>, anyhow::Error> {
+        let day_dir = dir.join(format!("day{day:02}"));
+        if !day_dir.is_dir() {
+            return Ok(vec![]);
+        }
+
+        let mut fixtures = vec![];
+        for entry in std::fs::read_dir(&day_dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|e| e == "txt") {
+                fixtures.push(Fixture::read(day, &path)?);
+            }
+        }
+        fixtures.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(fixtures)
+    }
+
+    /// Every fixture for days 1 to 25.
+    pub fn load_all(dir: &Path) -> Result<Vec<Fixture>, anyhow::Error> {
+        let mut out = vec![];
+        for day in 1..=25 {
+            out.extend(Fixture::load(dir, day)?);
+        }
+
+        Ok(out)
+    }
+
+    fn read(day: u8, path: &Path) -> Result<Fixture, anyhow::Error> {
+        let name = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .ok_or_else(|| anyhow!("bad fixture name {}", path.display()))?
+            .to_string();
+        let input = std::fs::read_to_string(path)?;
+
+        let sidecar = path.with_extension("expected");
+        let expected = std::fs::read_to_string(&sidecar)
+            .with_context(|| format!("reading {}", sidecar.display()))?;
+
+        let mut f = Fixture {
+            day,
+            name,
+            input,
+            part1: None,
+            part2: None,
+            params: BTreeMap::new(),
+        };
+
+        for (n, line) in expected.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let (k, v) = line.split_once('=').ok_or_else(|| {
+                anyhow!("{}:{}: expected 'key = value'", sidecar.display(), n + 1)
+            })?;
+            let (k, v) = (k.trim(), v.trim().to_string());
+            match k {
+                "part1" => f.part1 = Some(v),
+                "part2" => f.part2 = Some(v),
+                _ => {
+                    f.params.insert(k.to_string(), v);
+                }
+            }
+        }
+
+        Ok(f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn load_day18() {
+        let fs = Fixture::load(&Fixture::default_dir(), 18).unwrap();
+        assert_eq!(1, fs.len());
+
+        let f = &fs[0];
+        assert_eq!("sample", f.name);
+        assert!(f.input.starts_with("5,4\n4,2\n"));
+        assert_eq!(Some("22"), f.expected(Part::One));
+        assert_eq!(Some("6,1"), f.expected(Part::Two));
+        assert_eq!(Some("7"), f.params.get("width").map(|s| s.as_str()));
+    }
+
+    #[test]
+    fn missing_day() {
+        let dir = Fixture::default_dir().join("nonexistent");
+        assert_eq!(
+            Ok(0),
+            Fixture::load(&dir, 1).map(|v| v.len()).map_err(|_| ())
+        );
+    }
+}
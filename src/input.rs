@@ -0,0 +1,274 @@
+use std::{collections::HashMap, env, path::PathBuf};
+
+use anyhow::{anyhow, Context};
+use reqwest::header::COOKIE;
+
+/// Somewhere puzzle inputs come from.
+pub trait InputProvider {
+    fn input(&self, day: u8) -> Result<String, anyhow::Error>;
+}
+
+/// Reads `<dir>/<day>.txt`, and nothing else.
+#[derive(Clone, Debug)]
+pub struct FsProvider {
+    pub dir: PathBuf,
+}
+
+impl FsProvider {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        FsProvider { dir: dir.into() }
+    }
+
+    fn path(&self, day: u8) -> PathBuf {
+        self.dir.join(format!("{day}.txt"))
+    }
+}
+
+impl InputProvider for FsProvider {
+    fn input(&self, day: u8) -> Result<String, anyhow::Error> {
+        let path = self.path(day);
+        std::fs::read_to_string(&path).with_context(|| format!("reading {}", path.display()))
+    }
+}
+
+/// Downloads inputs from the puzzle site, optionally caching them on disk
+/// so each day is only fetched once.
+#[derive(Clone, Debug)]
+pub struct HttpProvider {
+    base_url: String,
+    year: u16,
+    session: Option<String>,
+    cache: Option<FsProvider>,
+}
+
+impl Default for HttpProvider {
+    fn default() -> Self {
+        HttpProvider {
+            base_url: "https://adventofcode.com".to_string(),
+            year: 2024,
+            session: None,
+            cache: None,
+        }
+    }
+}
+
+impl HttpProvider {
+    /// Configured from the environment: `SESSION` for the cookie, plus
+    /// optional `AOC_BASE_URL`, `AOC_YEAR` and `AOC_INPUT_DIR` overrides.
+    /// Caches into `inputs/` unless told otherwise.
+    pub fn from_env() -> Result<Self, anyhow::Error> {
+        let mut p = HttpProvider::default().cache_dir("inputs");
+        p.session = env::var("SESSION").ok();
+        if let Ok(url) = env::var("AOC_BASE_URL") {
+            p = p.base_url(url);
+        }
+        if let Ok(year) = env::var("AOC_YEAR") {
+            p = p.year(year.parse().context("AOC_YEAR should be a year")?);
+        }
+        if let Ok(dir) = env::var("AOC_INPUT_DIR") {
+            p = p.cache_dir(dir);
+        }
+
+        Ok(p)
+    }
+
+    pub fn base_url(mut self, url: impl Into<String>) -> Self {
+        self.base_url = url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn year(mut self, year: u16) -> Self {
+        self.year = year;
+        self
+    }
+
+    pub fn session(mut self, session: impl Into<String>) -> Self {
+        self.session = Some(session.into());
+        self
+    }
+
+    pub fn cache_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.cache = Some(FsProvider::new(dir));
+        self
+    }
+
+    pub fn no_cache(mut self) -> Self {
+        self.cache = None;
+        self
+    }
+
+    pub(crate) fn url(&self, path: &str) -> String {
+        format!("{}/{}/{path}", self.base_url, self.year)
+    }
+
+    pub(crate) fn session_cookie(&self) -> Result<String, anyhow::Error> {
+        let session = self
+            .session
+            .as_ref()
+            .ok_or_else(|| anyhow!("SESSION env var is required to talk to the server"))?;
+        Ok(format!("session={session}"))
+    }
+
+    fn download(&self, day: u8) -> Result<String, anyhow::Error> {
+        let client = reqwest::blocking::Client::new();
+        let resp = client
+            .get(self.url(&format!("day/{day}/input")))
+            .header(COOKIE, self.session_cookie()?)
+            .send()?
+            .error_for_status()?;
+
+        Ok(resp.text()?)
+    }
+}
+
+impl InputProvider for HttpProvider {
+    fn input(&self, day: u8) -> Result<String, anyhow::Error> {
+        let Some(cache) = &self.cache else {
+            return self.download(day);
+        };
+
+        if let Ok(s) = cache.input(day) {
+            return Ok(s);
+        }
+
+        let text = self.download(day)?;
+        std::fs::create_dir_all(&cache.dir)?;
+        std::fs::write(cache.path(day), &text)?;
+
+        Ok(text)
+    }
+}
+
+/// Inputs held in memory, for tests.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryProvider {
+    pub inputs: HashMap<u8, String>,
+}
+
+impl MemoryProvider {
+    pub fn with(mut self, day: u8, input: impl Into<String>) -> Self {
+        self.inputs.insert(day, input.into());
+        self
+    }
+}
+
+impl InputProvider for MemoryProvider {
+    fn input(&self, day: u8) -> Result<String, anyhow::Error> {
+        self.inputs
+            .get(&day)
+            .cloned()
+            .ok_or_else(|| anyhow!("no input for day {day}"))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        path::PathBuf,
+        thread::JoinHandle,
+    };
+
+    use super::*;
+
+    /// Serves a single canned response on a local port, handing back the
+    /// request head and body it received.
+    pub(crate) fn serve_once(
+        status: &'static str,
+        body: &'static str,
+    ) -> (String, JoinHandle<(String, String)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        let handle = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+            let mut head = String::new();
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some((k, v)) = line.split_once(':') {
+                    if k.eq_ignore_ascii_case("content-length") {
+                        content_length = v.trim().parse().unwrap();
+                    }
+                }
+                if line == "\r\n" {
+                    break;
+                }
+                head.push_str(&line);
+            }
+            let mut req_body = vec![0; content_length];
+            std::io::Read::read_exact(&mut reader, &mut req_body).unwrap();
+
+            write!(
+                stream,
+                "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+
+            (head, String::from_utf8(req_body).unwrap())
+        });
+
+        (url, handle)
+    }
+
+    pub(crate) fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("aoc24-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn memory() {
+        let p = MemoryProvider::default().with(3, "mul(2,4)");
+        assert_eq!("mul(2,4)", p.input(3).unwrap());
+        assert!(p.input(4).is_err());
+    }
+
+    #[test]
+    fn http_fetches_then_caches() {
+        let dir = temp_dir("http-cache");
+        let (url, server) = serve_once("200 OK", "1 2\n3 4\n");
+        let p = HttpProvider::default()
+            .base_url(url)
+            .year(2023)
+            .session("abc123")
+            .cache_dir(&dir);
+
+        assert_eq!("1 2\n3 4\n", p.input(7).unwrap());
+        let (head, _) = server.join().unwrap();
+        assert!(head.starts_with("GET /2023/day/7/input HTTP/1.1"));
+        assert!(head.contains("cookie: session=abc123"));
+
+        // no server this time, so this can only come from the cache
+        assert_eq!("1 2\n3 4\n", p.input(7).unwrap());
+        assert_eq!("1 2\n3 4\n", FsProvider::new(&dir).input(7).unwrap());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn http_errors_are_not_cached() {
+        let dir = temp_dir("http-error");
+        let (url, server) = serve_once("400 Bad Request", "Puzzle inputs differ by user.");
+        let p = HttpProvider::default()
+            .base_url(url)
+            .session("abc123")
+            .cache_dir(&dir);
+
+        assert!(p.input(1).is_err());
+        server.join().unwrap();
+        assert!(!dir.join("1.txt").exists());
+    }
+
+    #[test]
+    fn http_needs_session() {
+        let p = HttpProvider::default().base_url("http://127.0.0.1:9");
+        let e = p.input(1).unwrap_err();
+        assert!(e.to_string().contains("SESSION"));
+    }
+}
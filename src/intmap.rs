@@ -1,3 +1,10 @@
+use std::{
+    fmt::Display,
+    ops::{Index, IndexMut},
+};
+
+use crate::{Dir4, Dir8, Point};
+
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct IntMap<T> {
     pub cells: Vec<T>,
@@ -6,7 +13,6 @@ pub struct IntMap<T> {
 }
 
 impl<T: Default + Clone> IntMap<T> {
-    #[allow(dead_code)]
     pub fn new(width: usize, height: usize) -> IntMap<T> {
         let cells = vec![T::default(); width * height];
         IntMap {
@@ -15,7 +21,9 @@ impl<T: Default + Clone> IntMap<T> {
             height,
         }
     }
+}
 
+impl<T> IntMap<T> {
     pub fn from_raw<F: Fn(char) -> T>(input: &str, mapper: F) -> IntMap<T> {
         let width = input
             .trim()
@@ -39,28 +47,199 @@ impl<T: Default + Clone> IntMap<T> {
         }
     }
 
-    pub fn in_bounds(&self, co: (isize, isize)) -> bool {
+    pub fn in_bounds(&self, co: impl Into<Point>) -> bool {
+        let co: (isize, isize) = co.into().into();
         0 <= co.0 && co.0 < self.width as isize && 0 <= co.1 && co.1 < self.height as isize
     }
 
-    pub fn pt_to_idx(&self, co: (isize, isize)) -> isize {
-        co.1 * self.width as isize + co.0
+    pub fn pt_to_idx(&self, co: impl Into<Point>) -> isize {
+        let co = co.into();
+        co.y * self.width as isize + co.x
     }
 
     pub fn idx_to_pt(&self, idx: isize) -> (isize, isize) {
         (idx % self.width as isize, idx / self.width as isize)
     }
 
-    pub fn get(&self, co: (isize, isize)) -> Option<&T> {
+    pub fn get(&self, co: impl Into<Point>) -> Option<&T> {
+        let co = co.into();
         if !self.in_bounds(co) {
             return None;
         }
         self.cells.get(self.pt_to_idx(co) as usize)
     }
 
-    #[allow(dead_code)]
-    pub fn set(&mut self, co: (isize, isize), val: T) {
+    pub fn get_mut(&mut self, co: impl Into<Point>) -> Option<&mut T> {
+        let co = co.into();
+        if !self.in_bounds(co) {
+            return None;
+        }
+        let idx = self.pt_to_idx(co) as usize;
+        self.cells.get_mut(idx)
+    }
+
+    pub fn set(&mut self, co: impl Into<Point>, val: T) {
         let idx = self.pt_to_idx(co);
         self.cells[idx as usize] = val;
     }
+
+    /// The in-bounds orthogonal neighbours of `co`, clockwise from north.
+    pub fn neighbours4(&self, co: impl Into<Point>) -> impl Iterator<Item = (isize, isize)> + '_ {
+        let co = co.into();
+        Dir4::ALL
+            .into_iter()
+            .map(move |d| (co + d).into())
+            .filter(|&n| self.in_bounds(n))
+    }
+
+    /// As `neighbours4`, but including diagonals.
+    pub fn neighbours8(&self, co: impl Into<Point>) -> impl Iterator<Item = (isize, isize)> + '_ {
+        let co = co.into();
+        Dir8::ALL
+            .into_iter()
+            .map(move |d| (co + d).into())
+            .filter(|&n| self.in_bounds(n))
+    }
+
+    /// Every cell with its coordinates, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = ((isize, isize), &T)> {
+        self.cells
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (self.idx_to_pt(i as isize), v))
+    }
+
+    /// Every coordinate, row by row.
+    pub fn coords(&self) -> impl Iterator<Item = (isize, isize)> {
+        let (w, h) = (self.width as isize, self.height as isize);
+        (0..h).flat_map(move |y| (0..w).map(move |x| (x, y)))
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn row(&self, y: isize) -> impl Iterator<Item = ((isize, isize), &T)> {
+        (0..self.width as isize).map(move |x| ((x, y), &self[(x, y)]))
+    }
+
+    pub fn column(&self, x: isize) -> impl Iterator<Item = ((isize, isize), &T)> {
+        (0..self.height as isize).map(move |y| ((x, y), &self[(x, y)]))
+    }
+
+    /// Coordinates of the first cell (row by row) matching `pred`.
+    pub fn position<F: Fn(&T) -> bool>(&self, pred: F) -> Option<(isize, isize)> {
+        self.cells
+            .iter()
+            .position(pred)
+            .map(|i| self.idx_to_pt(i as isize))
+    }
+
+    /// Coordinates of the first cell equal to `val`.
+    pub fn find(&self, val: &T) -> Option<(isize, isize)>
+    where
+        T: PartialEq,
+    {
+        self.position(|v| v == val)
+    }
+
+    /// A same-sized map with `f` applied to every cell.
+    pub fn map<U, F: FnMut(&T) -> U>(&self, f: F) -> IntMap<U> {
+        IntMap {
+            cells: self.cells.iter().map(f).collect(),
+            width: self.width,
+            height: self.height,
+        }
+    }
+}
+
+impl<T, P: Into<Point>> Index<P> for IntMap<T> {
+    type Output = T;
+
+    fn index(&self, co: P) -> &T {
+        let co = co.into();
+        self.get(co)
+            .unwrap_or_else(|| panic!("{co:?} out of bounds"))
+    }
+}
+
+impl<T, P: Into<Point>> IndexMut<P> for IntMap<T> {
+    fn index_mut(&mut self, co: P) -> &mut T {
+        let co = co.into();
+        self.get_mut(co)
+            .unwrap_or_else(|| panic!("{co:?} out of bounds"))
+    }
+}
+
+/// Renders one character (or whatever the cell displays as) per cell,
+/// with a newline after each row.
+impl<T: Display> Display for IntMap<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.rows() {
+            for c in row {
+                write!(f, "{c}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const GRID: &str = "
+        ab.
+        .c#
+        ";
+
+    #[test]
+    fn neighbours() {
+        let m = IntMap::from_raw(GRID, |c| c);
+        assert_eq!(
+            vec![(1, 0), (0, 1)],
+            m.neighbours4((0, 0)).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![(1, 0), (1, 1), (0, 1)],
+            m.neighbours8((0, 0)).collect::<Vec<_>>()
+        );
+        assert_eq!(5, m.neighbours8((1, 0)).count());
+    }
+
+    #[test]
+    fn iterators() {
+        let m = IntMap::from_raw(GRID, |c| c);
+        assert_eq!(((2, 1), &'#'), m.iter().last().unwrap());
+        assert_eq!(
+            m.iter().map(|(co, _)| co).collect::<Vec<_>>(),
+            m.coords().collect::<Vec<_>>()
+        );
+        assert_eq!("ab.", m.rows().next().unwrap().iter().collect::<String>());
+        assert_eq!(
+            vec![((1, 0), &'b'), ((1, 1), &'c')],
+            m.column(1).collect::<Vec<_>>()
+        );
+        assert_eq!(".c#", m.row(1).map(|(_, c)| c).collect::<String>());
+    }
+
+    #[test]
+    fn lookup_and_update() {
+        let mut m = IntMap::from_raw(GRID, |c| c);
+        assert_eq!(Some((1, 1)), m.find(&'c'));
+        assert_eq!(Some((2, 0)), m.position(|&c| c == '.'));
+        assert_eq!(None, m.find(&'z'));
+
+        m[(2, 1)] = 'd';
+        *m.get_mut((0, 1)).unwrap() = 'e';
+        assert_eq!(None, m.get_mut((3, 0)));
+        assert_eq!('d', m[(2, 1)]);
+
+        assert_eq!('c', m[Point::new(1, 1)]);
+        assert_eq!(Some(&'c'), m.get(Point::new(1, 0) + Dir4::South));
+
+        let upper = m.map(|c| c.to_ascii_uppercase());
+        assert_eq!("AB.\nECD\n", upper.to_string());
+    }
 }
@@ -0,0 +1,204 @@
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Context};
+
+use crate::{Part, Verdict};
+
+/// One submitted answer and what the server said about it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+    pub day: u8,
+    pub part: Part,
+    pub verdict: Verdict,
+    pub answer: String,
+}
+
+/// Every answer we've submitted, kept on disk as `<day> <part> <verdict>
+/// <answer>` lines so that nothing gets submitted twice.
+#[derive(Clone, Debug, Default)]
+pub struct Ledger {
+    path: Option<PathBuf>,
+    pub entries: Vec<Entry>,
+}
+
+impl Ledger {
+    /// Load the ledger at `path`, which needn't exist yet. Anything
+    /// recorded later is appended to it.
+    pub fn load(path: impl Into<PathBuf>) -> Result<Ledger, anyhow::Error> {
+        let path = path.into();
+        let text = match std::fs::read_to_string(&path) {
+            Ok(t) => t,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e).with_context(|| format!("reading {}", path.display())),
+        };
+
+        let entries = Ledger::parse(&text).with_context(|| format!("in {}", path.display()))?;
+        Ok(Ledger {
+            path: Some(path),
+            entries,
+        })
+    }
+
+    pub fn parse(text: &str) -> Result<Vec<Entry>, anyhow::Error> {
+        let mut entries = vec![];
+        for (n, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let mut fields = line.splitn(4, char::is_whitespace).map(str::trim);
+            let mut field = |name| {
+                fields
+                    .next()
+                    .filter(|f| !f.is_empty())
+                    .ok_or_else(|| anyhow!("line {}: missing {name}", n + 1))
+            };
+
+            entries.push(Entry {
+                day: field("day")?.parse()?,
+                part: field("part")?.parse()?,
+                verdict: field("verdict")?.parse()?,
+                answer: field("answer")?.to_string(),
+            });
+        }
+
+        Ok(entries)
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    fn for_part(&self, day: u8, part: Part) -> impl Iterator<Item = &Entry> {
+        self.entries
+            .iter()
+            .filter(move |e| e.day == day && e.part == part)
+    }
+
+    /// The accepted answer for a part, if we have one.
+    pub fn correct(&self, day: u8, part: Part) -> Option<&str> {
+        self.for_part(day, part)
+            .find(|e| e.verdict == Verdict::Correct)
+            .map(|e| e.answer.as_str())
+    }
+
+    /// A reason not to submit `answer`, if the ledger already rules it
+    /// out: the part is solved, this exact answer was judged before, or
+    /// it's outside a too-high/too-low bound we've already hit.
+    pub fn objection(&self, day: u8, part: Part, answer: &str) -> Option<String> {
+        if let Some(c) = self.correct(day, part) {
+            return Some(format!("already solved with {c}"));
+        }
+
+        let num = answer.parse::<i128>().ok();
+        for e in self
+            .for_part(day, part)
+            .filter(|e| e.verdict.is_judgement())
+        {
+            if e.answer == answer {
+                return Some(format!("already tried {answer}: {}", e.verdict));
+            }
+
+            let (Some(n), Ok(prev)) = (num, e.answer.parse::<i128>()) else {
+                continue;
+            };
+            if e.verdict == Verdict::TooHigh && n >= prev {
+                return Some(format!("{answer} is not below {prev}, which was too high"));
+            }
+            if e.verdict == Verdict::TooLow && n <= prev {
+                return Some(format!("{answer} is not above {prev}, which was too low"));
+            }
+        }
+
+        None
+    }
+
+    /// Add a verdict, appending it to the file if there is one.
+    pub fn record(
+        &mut self,
+        day: u8,
+        part: Part,
+        answer: &str,
+        verdict: Verdict,
+    ) -> Result<(), anyhow::Error> {
+        let entry = Entry {
+            day,
+            part,
+            verdict,
+            answer: answer.to_string(),
+        };
+
+        if let Some(path) = &self.path {
+            let mut f = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)?;
+            writeln!(
+                f,
+                "{} {} {} {}",
+                entry.day, entry.part, entry.verdict, entry.answer
+            )?;
+        }
+        self.entries.push(entry);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::input::test::temp_dir;
+
+    #[test]
+    fn objections() {
+        let mut l = Ledger::default();
+        l.record(17, Part::Two, "500", Verdict::TooHigh).unwrap();
+        l.record(17, Part::Two, "100", Verdict::TooLow).unwrap();
+        l.record(17, Part::Two, "250", Verdict::Wrong).unwrap();
+        l.record(17, Part::Two, "300", Verdict::Wait(Duration::from_secs(30)))
+            .unwrap();
+
+        assert!(l.objection(17, Part::Two, "600").is_some());
+        assert!(l.objection(17, Part::Two, "500").is_some());
+        assert!(l.objection(17, Part::Two, "99").is_some());
+        assert!(l.objection(17, Part::Two, "250").is_some());
+        // a rate-limited submission was never judged
+        assert_eq!(None, l.objection(17, Part::Two, "300"));
+        assert_eq!(None, l.objection(17, Part::One, "600"));
+
+        l.record(17, Part::Two, "300", Verdict::Correct).unwrap();
+        assert_eq!(Some("300"), l.correct(17, Part::Two));
+        assert_eq!(
+            Some("already solved with 300".to_string()),
+            l.objection(17, Part::Two, "301")
+        );
+    }
+
+    #[test]
+    fn file_round_trip() {
+        let dir = temp_dir("ledger");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("answers.txt");
+
+        let mut l = Ledger::load(&path).unwrap();
+        assert!(l.entries.is_empty());
+        l.record(18, Part::Two, "36,10", Verdict::Correct).unwrap();
+        l.record(9, Part::One, "12", Verdict::TooLow).unwrap();
+
+        let l2 = Ledger::load(&path).unwrap();
+        assert_eq!(l.entries, l2.entries);
+        assert_eq!(
+            "18 2 correct 36,10\n9 1 too_low 12\n",
+            std::fs::read_to_string(&path).unwrap()
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
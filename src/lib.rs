@@ -1,6 +1,46 @@
 mod intmap;
+pub use intmap::IntMap;
+
+mod point;
+pub use point::{Dir4, Dir8, Point};
+
+pub mod parse;
+
+pub mod render;
+
+mod search;
+pub use search::{astar, bfs, dijkstra, distance_field, Paths};
+
 mod iterext;
 
+mod params;
+pub(crate) use params::params;
+pub use params::Params;
+
+mod error;
+pub use error::Error;
+
+mod bench;
+pub use bench::{bench, compare, from_json, to_json, CountingAlloc, Regression, Stage, Stats};
+
+mod fixtures;
+pub use fixtures::Fixture;
+
+mod input;
+pub use input::{FsProvider, HttpProvider, InputProvider, MemoryProvider};
+
+mod answers;
+pub use answers::{Answers, Expected};
+
+mod ledger;
+pub use ledger::{Entry, Ledger};
+
+mod submit;
+pub use submit::Verdict;
+
+mod solution;
+pub use solution::{solution, solutions, Answer, Part, Solution};
+
 mod day01;
 pub use day01::Day1;
 
@@ -32,7 +72,7 @@ mod day10;
 pub use day10::Day10;
 
 mod day11;
-pub use day11::Day11;
+pub use day11::{Day11, Day11Params};
 
 mod day12;
 pub use day12::Day12;
@@ -41,31 +81,34 @@ mod day13;
 pub use day13::Day13;
 
 mod day14;
-pub use day14::Day14;
+pub use day14::{Day14, Day14Params, FrameScore, Swarm};
 
 mod day15;
-pub use day15::Day15;
+pub use day15::{Day15, Move, Warehouse};
 
 mod day16;
-pub use day16::Day16;
+pub use day16::{Day16, Seats};
 
 mod day17;
-pub use day17::Day17;
+pub use day17::{
+    decode, Breakpoint, Compiled, Day17, Debugger, Event, Fault, Instr, Machine, Op, Operand,
+    QuineError, Shape, Stop, TraceEntry,
+};
 
 mod day18;
-pub use day18::Day18;
+pub use day18::{Day18, Day18Params};
 
 mod day19;
 pub use day19::Day19;
 
 mod day20;
-pub use day20::Day20;
+pub use day20::{Day20, Day20Params};
 
 mod day21;
-pub use day21::Day21;
+pub use day21::{Day21, Day21Params};
 
 mod day22;
-pub use day22::Day22;
+pub use day22::{Day22, Day22Params};
 
 mod day23;
 pub use day23::Day23;
@@ -76,34 +119,56 @@ pub use day24::Day24;
 mod day25;
 pub use day25::Day25;
 
-use std::{env, fmt::Debug};
-
-use reqwest::header::COOKIE;
+use std::fmt::Debug;
 
 pub trait Day<T: Debug + Eq> {
+    /// Tunable constants, defaulting to the real puzzle's; `()` for days
+    /// that have none.
+    type Params: Params;
+
     fn part1(input: &str) -> T;
     fn part2(input: &str) -> T;
-}
 
-pub fn fetch_input(day: u8) -> Result<String, anyhow::Error> {
-    let _ = std::fs::create_dir("inputs");
-    let existing = std::fs::read_to_string(format!("inputs/{day}.txt"));
+    /// Like `part1`, but reports bad input as an `Error` rather than
+    /// panicking. Only days with a checked parser override this.
+    fn try_part1(input: &str) -> Result<T, Error> {
+        Ok(Self::part1(input))
+    }
 
-    if let Ok(s) = existing {
-        return Ok(s);
+    fn try_part2(input: &str) -> Result<T, Error> {
+        Ok(Self::part2(input))
     }
 
-    let session = env::var("SESSION").expect("SESSION env var is required to fetch input");
-    let client = reqwest::blocking::Client::new();
-    let resp = client
-        .get(format!("https://adventofcode.com/2024/day/{day}/input"))
-        .header(COOKIE, format!("session={session}"))
-        .send()?;
+    /// Like `try_part1`, but with `params` in place of the defaults. Days
+    /// with parameters override this; the rest ignore them.
+    fn try_part1_with(input: &str, _params: &Self::Params) -> Result<T, Error> {
+        Self::try_part1(input)
+    }
+
+    fn try_part2_with(input: &str, _params: &Self::Params) -> Result<T, Error> {
+        Self::try_part2(input)
+    }
 
-    let text: String = resp.text()?;
-    std::fs::write(format!("inputs/{day}.txt"), &text)?;
+    /// Parse the input without solving anything, or `None` if the day has
+    /// no standalone parser to run.
+    fn validate(_input: &str) -> Option<Result<(), Error>> {
+        None
+    }
 
-    Ok(text)
+    /// A picture of the day's puzzle, for days that have something worth
+    /// looking at.
+    fn render(
+        _input: &str,
+        _params: &Self::Params,
+    ) -> Option<Result<IntMap<render::Style>, Error>> {
+        None
+    }
+}
+
+/// Fetch a day's input with the default provider: cached in `inputs/`,
+/// downloaded with the `SESSION` cookie on a miss.
+pub fn fetch_input(day: u8) -> Result<String, anyhow::Error> {
+    HttpProvider::from_env()?.input(day)
 }
 
 pub fn fetch_input_s(day: &str) -> Result<String, anyhow::Error> {
@@ -113,3 +178,18 @@ pub fn fetch_input_s(day: &str) -> Result<String, anyhow::Error> {
         .expect("ends with a number");
     fetch_input(s)
 }
+
+#[cfg(test)]
+pub(crate) mod test {
+    /// A fixed-seed linear congruential generator, for tests that want
+    /// varied but repeatable data. Each call gives 31 fresh bits.
+    pub(crate) fn lcg(seed: u64) -> impl FnMut() -> u64 {
+        let mut state = seed;
+        move || {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            state >> 33
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use anyhow::anyhow;
+
+/// A day's tunable constants. `Default` gives the values for the real
+/// puzzle input; examples and the runner override them by name.
+pub trait Params: Default + Clone + std::fmt::Debug {
+    /// Override one value, parsing it from a string.
+    fn set(&mut self, key: &str, value: &str) -> Result<(), anyhow::Error>;
+
+    /// The defaults with each of `overrides` applied in turn.
+    fn with<'a>(
+        overrides: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Result<Self, anyhow::Error> {
+        let mut p = Self::default();
+        for (k, v) in overrides {
+            p.set(k, v)?;
+        }
+        Ok(p)
+    }
+}
+
+impl Params for () {
+    fn set(&mut self, key: &str, _value: &str) -> Result<(), anyhow::Error> {
+        Err(anyhow!("unknown parameter '{key}': this day has none"))
+    }
+}
+
+/// Declare a day's parameter struct, with the default for each field and a
+/// `Params` impl that sets fields by name.
+macro_rules! params {
+    (
+        $(#[$meta:meta])*
+        pub struct $name:ident {
+            $($(#[$fmeta:meta])* pub $field:ident: $ty:ty = $default:expr,)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Debug, PartialEq)]
+        pub struct $name {
+            $($(#[$fmeta])* pub $field: $ty,)*
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                $name {
+                    $($field: $default,)*
+                }
+            }
+        }
+
+        impl $crate::Params for $name {
+            fn set(&mut self, key: &str, value: &str) -> Result<(), anyhow::Error> {
+                match key {
+                    $(stringify!($field) => {
+                        self.$field = value
+                            .parse()
+                            .map_err(|e| anyhow::anyhow!("bad value '{value}' for {key}: {e}"))?;
+                    })*
+                    _ => {
+                        let known = [$(stringify!($field)),*].join(", ");
+                        return Err(anyhow::anyhow!("unknown parameter '{key}', expected one of: {known}"));
+                    }
+                }
+                Ok(())
+            }
+        }
+    };
+}
+
+pub(crate) use params;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    params! {
+        pub struct Grid {
+            pub width: i64 = 101,
+            /// rows
+            pub height: i64 = 103,
+        }
+    }
+
+    #[test]
+    fn set_by_name() {
+        assert_eq!(
+            Grid {
+                width: 101,
+                height: 103
+            },
+            Grid::default()
+        );
+
+        let g = Grid::with([("height", "7"), ("width", "11")]).unwrap();
+        assert_eq!((11, 7), (g.width, g.height));
+
+        let e = Grid::with([("depth", "3")]).unwrap_err();
+        assert_eq!(
+            "unknown parameter 'depth', expected one of: width, height",
+            e.to_string()
+        );
+        assert!(Grid::with([("width", "wide")]).is_err());
+        assert!(<()>::with([("width", "1")]).is_err());
+    }
+}
@@ -0,0 +1,186 @@
+//! Helpers for the usual shapes of puzzle input. Everything here hands back
+//! slices of the string it was given, so a bad token can still be reported
+//! with [`Error::parse`] against the original input. Line endings may be
+//! `\n` or `\r\n`.
+
+use std::str::FromStr;
+
+use crate::{Error, IntMap};
+
+/// The blocks of `input` separated by blank (or whitespace-only) lines,
+/// each trimmed.
+pub fn sections(input: &str) -> Vec<&str> {
+    let mut out = vec![];
+    let mut start = None;
+    let mut end = 0;
+    let mut pos = 0;
+
+    for line in input.split_inclusive('\n') {
+        if line.trim().is_empty() {
+            if let Some(s) = start.take() {
+                out.push(input[s..end].trim());
+            }
+        } else {
+            start.get_or_insert(pos);
+            end = pos + line.len();
+        }
+        pos += line.len();
+    }
+    if let Some(s) = start {
+        out.push(input[s..end].trim());
+    }
+
+    out
+}
+
+/// The non-blank lines of `input`, trimmed.
+pub fn lines(input: &str) -> impl Iterator<Item = &str> {
+    input.lines().map(str::trim).filter(|l| !l.is_empty())
+}
+
+/// Every integer in `s`, in order, ignoring whatever is between them. A
+/// `-` directly before a number makes it negative. Fails with the first
+/// token that doesn't fit in a `T`.
+pub fn ints<T: FromStr>(s: &str) -> Result<Vec<T>, &str> {
+    int_tokens(s).map(|t| t.parse().map_err(|_| t)).collect()
+}
+
+/// As [`ints`], but the matching slices of `s` rather than their values.
+pub fn int_tokens(s: &str) -> impl Iterator<Item = &str> {
+    let bytes = s.as_bytes();
+    let mut i = 0;
+
+    std::iter::from_fn(move || {
+        while i < bytes.len() && !bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+        if i == bytes.len() {
+            return None;
+        }
+
+        let start = if i > 0 && bytes[i - 1] == b'-' {
+            i - 1
+        } else {
+            i
+        };
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+
+        Some(&s[start..i])
+    })
+}
+
+/// `s` with each integer replaced by `#`, to check the text around them:
+/// `"p=0,4 v=3,-3"` becomes `"p=#,# v=#,#"`.
+pub fn skeleton(s: &str) -> String {
+    let mut out = String::new();
+    let mut last = 0;
+    for t in int_tokens(s) {
+        let start = t.as_ptr() as usize - s.as_ptr() as usize;
+        out += &s[last..start];
+        out.push('#');
+        last = start + t.len();
+    }
+    out + &s[last..]
+}
+
+/// Split `line` once on `sep` and parse both halves, trimmed.
+pub fn pair<A: FromStr, B: FromStr>(line: &str, sep: &str) -> Option<(A, B)> {
+    let (a, b) = line.split_once(sep)?;
+    Some((a.trim().parse().ok()?, b.trim().parse().ok()?))
+}
+
+/// Read a rectangular block of characters into an `IntMap`, reporting the
+/// first character `cell` rejects (or a ragged row) as a parse error for
+/// `day`. `block` must be a slice of `input`.
+pub fn grid<T>(
+    day: u8,
+    input: &str,
+    block: &str,
+    expected: &'static str,
+    cell: impl Fn(char) -> Option<T>,
+) -> Result<IntMap<T>, Error> {
+    let mut cells = vec![];
+    let mut width = None;
+    let mut height = 0;
+
+    for line in lines(block) {
+        for (i, c) in line.char_indices() {
+            let token = &line[i..i + c.len_utf8()];
+            cells.push(cell(c).ok_or_else(|| Error::parse(day, input, token, expected))?);
+        }
+
+        let w = line.chars().count();
+        if *width.get_or_insert(w) != w {
+            return Err(Error::parse(day, input, line, "a row as wide as the first"));
+        }
+        height += 1;
+    }
+
+    Ok(IntMap {
+        cells,
+        width: width.ok_or(Error::truncated(day, "a grid"))?,
+        height,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn split_sections() {
+        let input = "a\r\nb\r\n\r\nc\r\n  \r\n\r\nd\n";
+        assert_eq!(vec!["a\r\nb", "c", "d"], sections(input));
+        assert_eq!(
+            vec!["a", "b"],
+            lines(sections(input)[0]).collect::<Vec<_>>()
+        );
+        assert!(sections("\n\n").is_empty());
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(Ok(vec![0, 4, 3, -3]), ints::<i64>("p=0,4 v=3,-3"));
+        assert_eq!(
+            Ok(vec![94, 34, 8400]),
+            ints::<u32>("Button A: X+94, Y+34\nPrize: X=8400")
+        );
+        assert_eq!(Err("-5"), ints::<u32>("x=3, y=-5"));
+        assert_eq!(Err("300"), ints::<u8>("255 300"));
+        assert_eq!("p=#,# v=#,#", skeleton("p=0,4 v=3,-3"));
+        assert_eq!("x", skeleton("x"));
+        assert_eq!(vec!["-12", "5"], int_tokens("x-12 y5").collect::<Vec<_>>());
+        assert_eq!(Some((3, 4)), pair::<u8, i32>(" 3 , 4 ", ","));
+        assert_eq!(None, pair::<u8, u8>("3,x", ","));
+    }
+
+    #[test]
+    fn grids() {
+        let input = "header\r\n\r\n#.\r\n.#\r\n";
+        let block = sections(input)[1];
+        let m = grid(1, input, block, "# or .", |c| match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        })
+        .unwrap();
+        assert_eq!((2, 2), (m.width, m.height));
+        assert_eq!(vec![true, false, false, true], m.cells);
+
+        let bad = "#.\n.x\n";
+        let err = grid(1, bad, bad, "# or .", |c| (c != 'x').then_some(c));
+        assert!(matches!(
+            err,
+            Err(Error::Parse {
+                line: 2,
+                column: 2,
+                ..
+            })
+        ));
+
+        let ragged = "#.\n.\n";
+        assert!(grid(1, ragged, ragged, "# or .", Some).is_err());
+    }
+}
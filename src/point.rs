@@ -0,0 +1,249 @@
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+use nalgebra::Vector2;
+
+/// A grid position or offset; `y` grows downwards, as in the puzzle inputs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point {
+    pub x: isize,
+    pub y: isize,
+}
+
+impl Point {
+    pub const fn new(x: isize, y: isize) -> Point {
+        Point { x, y }
+    }
+
+    pub fn manhattan(self, other: Point) -> usize {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+
+    pub fn chebyshev(self, other: Point) -> usize {
+        self.x.abs_diff(other.x).max(self.y.abs_diff(other.y))
+    }
+}
+
+impl Add for Point {
+    type Output = Point;
+
+    fn add(self, rhs: Point) -> Point {
+        Point::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl Sub for Point {
+    type Output = Point;
+
+    fn sub(self, rhs: Point) -> Point {
+        Point::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl AddAssign for Point {
+    fn add_assign(&mut self, rhs: Point) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for Point {
+    fn sub_assign(&mut self, rhs: Point) {
+        *self = *self - rhs;
+    }
+}
+
+impl Mul<isize> for Point {
+    type Output = Point;
+
+    fn mul(self, rhs: isize) -> Point {
+        Point::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+impl Neg for Point {
+    type Output = Point;
+
+    fn neg(self) -> Point {
+        Point::new(-self.x, -self.y)
+    }
+}
+
+impl Add<Dir4> for Point {
+    type Output = Point;
+
+    fn add(self, rhs: Dir4) -> Point {
+        self + rhs.delta()
+    }
+}
+
+impl Add<Dir8> for Point {
+    type Output = Point;
+
+    fn add(self, rhs: Dir8) -> Point {
+        self + rhs.delta()
+    }
+}
+
+impl From<(isize, isize)> for Point {
+    fn from((x, y): (isize, isize)) -> Point {
+        Point::new(x, y)
+    }
+}
+
+impl From<Point> for (isize, isize) {
+    fn from(p: Point) -> (isize, isize) {
+        (p.x, p.y)
+    }
+}
+
+impl From<Vector2<i64>> for Point {
+    fn from(v: Vector2<i64>) -> Point {
+        Point::new(v.x as isize, v.y as isize)
+    }
+}
+
+impl From<Point> for Vector2<i64> {
+    fn from(p: Point) -> Vector2<i64> {
+        Vector2::new(p.x as i64, p.y as i64)
+    }
+}
+
+/// The four orthogonal directions, in clockwise order from north.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Dir4 {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Dir4 {
+    pub const ALL: [Dir4; 4] = [Dir4::North, Dir4::East, Dir4::South, Dir4::West];
+
+    pub fn clockwise(self) -> Dir4 {
+        Dir4::ALL[(self as usize + 1) % 4]
+    }
+
+    pub fn anticlockwise(self) -> Dir4 {
+        Dir4::ALL[(self as usize + 3) % 4]
+    }
+
+    pub fn reverse(self) -> Dir4 {
+        Dir4::ALL[(self as usize + 2) % 4]
+    }
+
+    pub fn delta(self) -> Point {
+        match self {
+            Dir4::North => Point::new(0, -1),
+            Dir4::East => Point::new(1, 0),
+            Dir4::South => Point::new(0, 1),
+            Dir4::West => Point::new(-1, 0),
+        }
+    }
+
+    /// Read one of `^>v<`.
+    pub fn from_arrow(c: char) -> Option<Dir4> {
+        match c {
+            '^' => Some(Dir4::North),
+            '>' => Some(Dir4::East),
+            'v' => Some(Dir4::South),
+            '<' => Some(Dir4::West),
+            _ => None,
+        }
+    }
+}
+
+/// The eight compass directions, in clockwise order from north.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Dir8 {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Dir8 {
+    pub const ALL: [Dir8; 8] = [
+        Dir8::North,
+        Dir8::NorthEast,
+        Dir8::East,
+        Dir8::SouthEast,
+        Dir8::South,
+        Dir8::SouthWest,
+        Dir8::West,
+        Dir8::NorthWest,
+    ];
+
+    /// An eighth of a turn clockwise.
+    pub fn clockwise(self) -> Dir8 {
+        Dir8::ALL[(self as usize + 1) % 8]
+    }
+
+    pub fn anticlockwise(self) -> Dir8 {
+        Dir8::ALL[(self as usize + 7) % 8]
+    }
+
+    pub fn reverse(self) -> Dir8 {
+        Dir8::ALL[(self as usize + 4) % 8]
+    }
+
+    pub fn delta(self) -> Point {
+        match self {
+            Dir8::North => Point::new(0, -1),
+            Dir8::NorthEast => Point::new(1, -1),
+            Dir8::East => Point::new(1, 0),
+            Dir8::SouthEast => Point::new(1, 1),
+            Dir8::South => Point::new(0, 1),
+            Dir8::SouthWest => Point::new(-1, 1),
+            Dir8::West => Point::new(-1, 0),
+            Dir8::NorthWest => Point::new(-1, -1),
+        }
+    }
+}
+
+impl From<Dir4> for Dir8 {
+    fn from(d: Dir4) -> Dir8 {
+        Dir8::ALL[d as usize * 2]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn arithmetic() {
+        let a = Point::new(3, -2);
+        let b = Point::from((1, 4));
+        assert_eq!(Point::new(4, 2), a + b);
+        assert_eq!(Point::new(2, -6), a - b);
+        assert_eq!(Point::new(-6, 4), -a * 2);
+        assert_eq!(8, a.manhattan(b));
+        assert_eq!(6, a.chebyshev(b));
+        assert_eq!(Point::new(3, -3), a + Dir4::North);
+
+        let v: Vector2<i64> = a.into();
+        assert_eq!(Vector2::new(3, -2), v);
+        assert_eq!(a, Point::from(v));
+    }
+
+    #[test]
+    fn turning() {
+        for d in Dir4::ALL {
+            assert_eq!(d, d.clockwise().anticlockwise());
+            assert_eq!(d.reverse(), d.clockwise().clockwise());
+            assert_eq!(-d.delta(), d.reverse().delta());
+            assert_eq!(d.delta(), Dir8::from(d).delta());
+        }
+        for d in Dir8::ALL {
+            assert_eq!(d, d.clockwise().anticlockwise());
+            assert_eq!(-d.delta(), d.reverse().delta());
+        }
+        assert_eq!(Dir4::West, Dir4::North.anticlockwise());
+        assert_eq!(Dir8::SouthWest, Dir8::NorthEast.reverse());
+        assert_eq!(Some(Dir4::South), Dir4::from_arrow('v'));
+    }
+}
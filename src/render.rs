@@ -0,0 +1,267 @@
+//! Drawing grids for a human to look at: a map is first turned into an
+//! `IntMap<Style>`, which can then be written out as plain text, as text
+//! with ANSI colours for a terminal, or as a PNG.
+
+use std::{fmt::Display, io::Write, path::Path};
+
+use flate2::{write::ZlibEncoder, Compression};
+
+use crate::{IntMap, Point};
+
+pub type Rgb = [u8; 3];
+
+pub const BLACK: Rgb = [0, 0, 0];
+pub const WHITE: Rgb = [255, 255, 255];
+pub const GREY: Rgb = [128, 128, 128];
+pub const RED: Rgb = [220, 50, 47];
+pub const GREEN: Rgb = [133, 153, 0];
+pub const YELLOW: Rgb = [181, 137, 0];
+pub const BLUE: Rgb = [38, 139, 210];
+
+/// How one cell is drawn: a character for text output, with optional
+/// colours for the terminal and for images.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Style {
+    pub glyph: char,
+    pub fg: Option<Rgb>,
+    pub bg: Option<Rgb>,
+}
+
+impl Style {
+    pub const fn plain(glyph: char) -> Style {
+        Style {
+            glyph,
+            fg: None,
+            bg: None,
+        }
+    }
+
+    pub const fn fg(self, colour: Rgb) -> Style {
+        Style {
+            fg: Some(colour),
+            ..self
+        }
+    }
+
+    pub const fn bg(self, colour: Rgb) -> Style {
+        Style {
+            bg: Some(colour),
+            ..self
+        }
+    }
+
+    /// The colour of the cell's pixels in an image: its background, else
+    /// its foreground, else black for `.` and spaces and white otherwise.
+    pub fn pixel(&self) -> Rgb {
+        self.bg.or(self.fg).unwrap_or(match self.glyph {
+            '.' | ' ' => BLACK,
+            _ => WHITE,
+        })
+    }
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Style::plain('.')
+    }
+}
+
+impl Display for Style {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.glyph)
+    }
+}
+
+/// A `width` x `height` canvas of `blank` with each of `cells` drawn on
+/// top. Cells outside the canvas are dropped.
+pub fn sparse<P: Into<Point>>(
+    width: usize,
+    height: usize,
+    blank: Style,
+    cells: impl IntoIterator<Item = (P, Style)>,
+) -> IntMap<Style> {
+    let mut m = IntMap {
+        cells: vec![blank; width * height],
+        width,
+        height,
+    };
+    m.overlay(cells);
+    m
+}
+
+impl IntMap<Style> {
+    /// Draw each of `cells` over what's already there, ignoring any that
+    /// are out of bounds.
+    pub fn overlay<P: Into<Point>>(&mut self, cells: impl IntoIterator<Item = (P, Style)>) {
+        for (p, s) in cells {
+            if let Some(c) = self.get_mut(p) {
+                *c = s;
+            }
+        }
+    }
+
+    /// Just the glyphs, a line per row.
+    pub fn ascii(&self) -> String {
+        self.to_string()
+    }
+
+    /// The glyphs with 24-bit colour escapes, for a terminal.
+    pub fn ansi(&self) -> String {
+        let mut out = String::new();
+        for row in self.rows() {
+            let mut current = (None, None);
+            for s in row {
+                if (s.fg, s.bg) != current {
+                    if current != (None, None) {
+                        out.push_str("\x1b[0m");
+                    }
+                    if let Some([r, g, b]) = s.fg {
+                        out += &format!("\x1b[38;2;{r};{g};{b}m");
+                    }
+                    if let Some([r, g, b]) = s.bg {
+                        out += &format!("\x1b[48;2;{r};{g};{b}m");
+                    }
+                    current = (s.fg, s.bg);
+                }
+                out.push(s.glyph);
+            }
+            if current != (None, None) {
+                out.push_str("\x1b[0m");
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// An RGB PNG with each cell drawn as a `scale` x `scale` square.
+    pub fn png(&self, scale: usize) -> Vec<u8> {
+        let scale = scale.max(1);
+        let (w, h) = (self.width * scale, self.height * scale);
+
+        let mut raw = Vec::with_capacity(h * (w * 3 + 1));
+        for row in self.rows() {
+            let mut line = vec![0]; // no filter
+            for s in row {
+                for _ in 0..scale {
+                    line.extend(s.pixel());
+                }
+            }
+            for _ in 0..scale {
+                raw.extend(&line);
+            }
+        }
+
+        let mut z = ZlibEncoder::new(vec![], Compression::default());
+        z.write_all(&raw).expect("writing to a Vec");
+        let data = z.finish().expect("writing to a Vec");
+
+        let mut header = vec![];
+        header.extend((w as u32).to_be_bytes());
+        header.extend((h as u32).to_be_bytes());
+        // 8 bits per channel, RGB, deflate, no filtering, no interlace
+        header.extend([8, 2, 0, 0, 0]);
+
+        let mut out = b"\x89PNG\r\n\x1a\n".to_vec();
+        chunk(&mut out, b"IHDR", &header);
+        chunk(&mut out, b"IDAT", &data);
+        chunk(&mut out, b"IEND", &[]);
+        out
+    }
+
+    /// Write a PNG if `path` ends in `.png`, or plain text otherwise.
+    pub fn save(&self, path: impl AsRef<Path>, scale: usize) -> std::io::Result<()> {
+        let path = path.as_ref();
+        if path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("png"))
+        {
+            std::fs::write(path, self.png(scale))
+        } else {
+            std::fs::write(path, self.ascii())
+        }
+    }
+}
+
+fn chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend((data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend(kind);
+    out.extend(data);
+    let crc = crc32(&out[start..]);
+    out.extend(crc.to_be_bytes());
+}
+
+/// The CRC-32 that PNG chunks (and zip, and gzip) use.
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &b in bytes {
+        crc ^= b as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Read;
+
+    use flate2::read::ZlibDecoder;
+
+    use super::*;
+
+    fn sample() -> IntMap<Style> {
+        let wall = Style::plain('#').fg(GREY);
+        sparse(3, 2, Style::default(), [((0, 0), wall), ((2, 1), wall)])
+    }
+
+    #[test]
+    fn text() {
+        let mut m = sample();
+        assert_eq!("#..\n..#\n", m.ascii());
+
+        m.overlay([
+            ((1, 1), Style::plain('@').fg(RED)),
+            ((5, 5), Style::plain('!')),
+        ]);
+        assert_eq!("#..\n.@#\n", m.ascii());
+        assert_eq!(
+            "\x1b[38;2;128;128;128m#\x1b[0m..\n\
+             .\x1b[38;2;220;50;47m@\x1b[0m\x1b[38;2;128;128;128m#\x1b[0m\n",
+            m.ansi()
+        );
+    }
+
+    #[test]
+    fn crc() {
+        assert_eq!(0xcbf4_3926, crc32(b"123456789"));
+        assert_eq!(0xae42_6082, crc32(b"IEND"));
+    }
+
+    #[test]
+    fn png() {
+        let png = sample().png(2);
+        assert_eq!(b"\x89PNG\r\n\x1a\n", &png[..8]);
+        assert_eq!(b"IHDR", &png[12..16]);
+        assert_eq!([0, 0, 0, 6, 0, 0, 0, 4], png[16..24]);
+        assert_eq!(b"IEND\xae\x42\x60\x82", &png[png.len() - 8..]);
+
+        let idat_len = u32::from_be_bytes(png[33..37].try_into().unwrap()) as usize;
+        assert_eq!(b"IDAT", &png[37..41]);
+        let mut raw = vec![];
+        ZlibDecoder::new(&png[41..41 + idat_len])
+            .read_to_end(&mut raw)
+            .unwrap();
+
+        // 4 rows, each a filter byte then 6 pixels
+        assert_eq!(4 * 19, raw.len());
+        assert_eq!([0, 128, 128, 128, 128, 128, 128, 0, 0, 0], raw[..10]);
+        assert_eq!(raw[..19], raw[19..38]);
+        assert_eq!(GREY, raw[73..76]);
+    }
+}
@@ -0,0 +1,408 @@
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+    hash::Hash,
+};
+
+use crate::{IntMap, Point};
+
+/// Distances for every state a search reached, along with each state's
+/// predecessors on its shortest routes.
+#[derive(Clone, Debug)]
+pub struct Paths<S> {
+    dist: HashMap<S, u64>,
+    prev: HashMap<S, Vec<S>>,
+}
+
+impl<S: Clone + Eq + Hash> Paths<S> {
+    pub fn distance(&self, s: &S) -> Option<u64> {
+        self.dist.get(s).copied()
+    }
+
+    /// A shortest route from a start state to `to`, inclusive of both ends.
+    pub fn path(&self, to: &S) -> Option<Vec<S>> {
+        self.dist.get(to)?;
+
+        let mut out = vec![to.clone()];
+        while let Some(p) = self.predecessors(out.last().expect("never empty")).first() {
+            out.push(p.clone());
+        }
+        out.reverse();
+
+        Some(out)
+    }
+
+    /// The states one step before `s` on any of its shortest routes; empty
+    /// for a start state (or one the search never reached).
+    pub fn predecessors(&self, s: &S) -> &[S] {
+        self.prev.get(s).map_or(&[], |p| p.as_slice())
+    }
+
+    /// Every state on at least one shortest route to any of `targets`,
+    /// including the targets themselves.
+    pub fn ancestors<'a>(&self, targets: impl IntoIterator<Item = &'a S>) -> HashSet<S>
+    where
+        S: 'a,
+    {
+        let mut seen = HashSet::new();
+        let mut stack: Vec<&S> = targets
+            .into_iter()
+            .filter(|t| self.dist.contains_key(t))
+            .collect();
+
+        while let Some(s) = stack.pop() {
+            if seen.insert(s.clone()) {
+                stack.extend(self.predecessors(s));
+            }
+        }
+
+        seen
+    }
+
+    /// How many distinct shortest routes (as sequences of states) lead to
+    /// any of `targets`. Assumes every step costs more than nothing.
+    pub fn count_paths<'a>(&self, targets: impl IntoIterator<Item = &'a S> + Clone) -> u64
+    where
+        S: 'a,
+    {
+        let mut order: Vec<S> = self.ancestors(targets.clone()).into_iter().collect();
+        order.sort_by_key(|s| self.dist[s]);
+
+        // predecessors are always strictly closer, so they're counted first
+        let mut counts = HashMap::<&S, u64>::new();
+        for s in &order {
+            let preds = self.predecessors(s);
+            let n = if preds.is_empty() {
+                1
+            } else {
+                preds.iter().map(|p| counts[p]).fold(0, u64::saturating_add)
+            };
+            counts.insert(s, n);
+        }
+
+        targets
+            .into_iter()
+            .filter_map(|t| counts.get(t))
+            .fold(0, |a, &b| a.saturating_add(b))
+    }
+
+    pub fn distances(&self) -> &HashMap<S, u64> {
+        &self.dist
+    }
+
+    pub fn into_distances(self) -> HashMap<S, u64> {
+        self.dist
+    }
+}
+
+/// Steps from `from` to every cell of `m` reachable through cells that
+/// are `passable`, moving orthogonally.
+pub fn distance_field<T>(
+    m: &IntMap<T>,
+    from: impl Into<Point>,
+    passable: impl Fn(&T) -> bool,
+) -> IntMap<Option<u32>> {
+    let mut field = m.map(|_| None);
+    let from: (isize, isize) = from.into().into();
+    if !m.get(from).is_some_and(&passable) {
+        return field;
+    }
+
+    let mut q = VecDeque::from([(from, 0)]);
+    field[from] = Some(0);
+    while let Some((p, d)) = q.pop_front() {
+        for n in m.neighbours4(p) {
+            if field[n].is_none() && passable(&m[n]) {
+                field[n] = Some(d + 1);
+                q.push_back((n, d + 1));
+            }
+        }
+    }
+
+    field
+}
+
+/// Min-heap entry ordered on cost alone, so states needn't be `Ord`.
+struct Queued<S>(u64, S);
+
+impl<S> PartialEq for Queued<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<S> Eq for Queued<S> {}
+
+impl<S> Ord for Queued<S> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.0.cmp(&self.0)
+    }
+}
+
+impl<S> PartialOrd for Queued<S> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Breadth-first search where every step costs 1, exploring everything
+/// reachable from `starts`.
+pub fn bfs<S, I>(
+    starts: impl IntoIterator<Item = S>,
+    mut neighbours: impl FnMut(&S) -> I,
+) -> Paths<S>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+{
+    let mut dist = HashMap::new();
+    let mut prev = HashMap::new();
+    let mut q = VecDeque::new();
+
+    for s in starts {
+        if dist.insert(s.clone(), 0).is_none() {
+            q.push_back(s);
+        }
+    }
+
+    while let Some(s) = q.pop_front() {
+        let d = dist[&s] + 1;
+        for n in neighbours(&s) {
+            match dist.get(&n) {
+                Some(&nd) if nd == d => prev.entry(n).or_insert_with(Vec::new).push(s.clone()),
+                Some(_) => {}
+                None => {
+                    dist.insert(n.clone(), d);
+                    prev.insert(n.clone(), vec![s.clone()]);
+                    q.push_back(n);
+                }
+            }
+        }
+    }
+
+    Paths { dist, prev }
+}
+
+/// Dijkstra's algorithm over weighted edges, exploring everything
+/// reachable from `starts`.
+pub fn dijkstra<S, I>(
+    starts: impl IntoIterator<Item = S>,
+    mut neighbours: impl FnMut(&S) -> I,
+) -> Paths<S>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = (S, u64)>,
+{
+    let mut dist = HashMap::new();
+    let mut prev: HashMap<S, Vec<S>> = HashMap::new();
+    let mut done = HashSet::new();
+    let mut q = BinaryHeap::new();
+
+    for s in starts {
+        dist.insert(s.clone(), 0);
+        q.push(Queued(0, s));
+    }
+
+    while let Some(Queued(cost, s)) = q.pop() {
+        if cost > dist[&s] || !done.insert(s.clone()) {
+            continue;
+        }
+
+        for (n, step) in neighbours(&s) {
+            let next = cost + step;
+            match dist.get(&n) {
+                Some(&d) if d < next => {}
+                Some(&d) if d == next => prev.entry(n).or_default().push(s.clone()),
+                _ => {
+                    dist.insert(n.clone(), next);
+                    prev.insert(n.clone(), vec![s.clone()]);
+                    q.push(Queued(next, n));
+                }
+            }
+        }
+    }
+
+    Paths { dist, prev }
+}
+
+/// A* from `start` to the nearest state satisfying `is_goal`, returning the
+/// cost and the route. `heuristic` must never overestimate the remaining
+/// cost, but needn't be consistent: a state found again more cheaply is
+/// just expanded again. With `|_| 0` this is a Dijkstra that stops at the
+/// goal.
+pub fn astar<S, I>(
+    start: S,
+    mut neighbours: impl FnMut(&S) -> I,
+    mut heuristic: impl FnMut(&S) -> u64,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Option<(u64, Vec<S>)>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = (S, u64)>,
+{
+    let mut dist = HashMap::new();
+    let mut prev = HashMap::new();
+    let mut q = BinaryHeap::new();
+
+    dist.insert(start.clone(), 0);
+    q.push(Queued(heuristic(&start), (0, start)));
+
+    while let Some(Queued(_, (cost, s))) = q.pop() {
+        // queued again since, at a lower cost
+        if cost > dist[&s] {
+            continue;
+        }
+        if is_goal(&s) {
+            let paths = Paths { dist, prev };
+            return Some((cost, paths.path(&s).expect("reached")));
+        }
+
+        for (n, step) in neighbours(&s) {
+            let next = cost + step;
+            if dist.get(&n).is_some_and(|&d| d <= next) {
+                continue;
+            }
+            dist.insert(n.clone(), next);
+            prev.insert(n.clone(), vec![s.clone()]);
+            q.push(Queued(next + heuristic(&n), (next, n)));
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const MAZE: &str = "
+        S.#.
+        .##.
+        ...E
+        ";
+
+    fn open(m: &IntMap<char>, p: (isize, isize)) -> Vec<(isize, isize)> {
+        m.neighbours4(p).filter(|&n| m[n] != '#').collect()
+    }
+
+    #[test]
+    fn grid() {
+        let m = IntMap::from_raw(MAZE, |c| c);
+        let (s, e) = (m.find(&'S').unwrap(), m.find(&'E').unwrap());
+
+        let b = bfs([s], |&p| open(&m, p));
+        assert_eq!(Some(5), b.distance(&e));
+        assert_eq!(None, b.distance(&(2, 0)));
+        assert_eq!(
+            vec![(0, 0), (0, 1), (0, 2), (1, 2), (2, 2), (3, 2)],
+            b.path(&e).unwrap()
+        );
+
+        let d = dijkstra([s], |&p| open(&m, p).into_iter().map(|n| (n, 1)));
+        assert_eq!(b.distances(), d.distances());
+
+        let f = distance_field(&m, s, |&c| c != '#');
+        assert_eq!(Some(5), f[e]);
+        assert_eq!(None, f[(2, 0)]);
+        assert_eq!(None, f[(1, 1)]);
+        for (p, d) in b.distances() {
+            assert_eq!(Some(*d as u32), f[*p]);
+        }
+
+        let manhattan = |p: &(isize, isize)| p.0.abs_diff(e.0) as u64 + p.1.abs_diff(e.1) as u64;
+        let (cost, path) = astar(
+            s,
+            |&p| open(&m, p).into_iter().map(|n| (n, 1)),
+            manhattan,
+            |&p| p == e,
+        )
+        .unwrap();
+        assert_eq!(5, cost);
+        assert_eq!(b.path(&e).unwrap(), path);
+
+        assert_eq!(
+            None,
+            astar(
+                s,
+                |&p| open(&m, p).into_iter().map(|n| (n, 1)),
+                |_| 0,
+                |&p| p == (2, 0)
+            )
+        );
+    }
+
+    #[test]
+    fn weighted() {
+        // a direct edge that's dearer than the long way round
+        let edges = |&n: &u8| match n {
+            0 => vec![(1, 10), (2, 1)],
+            2 => vec![(3, 1)],
+            3 => vec![(1, 1)],
+            _ => vec![],
+        };
+
+        let d = dijkstra([0], edges);
+        assert_eq!(Some(3), d.distance(&1));
+        assert_eq!(1, d.count_paths([&1]));
+        assert_eq!(Some(vec![0, 2, 3, 1]), d.path(&1));
+        assert_eq!(
+            Some((3, vec![0, 2, 3, 1])),
+            astar(0, edges, |_| 0, |&n| n == 1)
+        );
+    }
+
+    #[test]
+    fn inconsistent_heuristic() {
+        // the estimate from 1 is fair but much higher than from its
+        // neighbour 2, so 2 and then 3 are first reached the dear way
+        let edges = |&n: &u8| match n {
+            0 => vec![(1, 1), (2, 4)],
+            1 => vec![(2, 1)],
+            2 => vec![(3, 10)],
+            3 => vec![(4, 10)],
+            _ => vec![],
+        };
+        let estimate = |&n: &u8| if n == 1 { 10 } else { 0 };
+
+        let mut expanded = HashMap::<u8, u32>::new();
+        let res = astar(
+            0,
+            |n| {
+                *expanded.entry(*n).or_default() += 1;
+                edges(n)
+            },
+            estimate,
+            |&n| n == 4,
+        );
+        assert_eq!(Some((22, vec![0, 1, 2, 3, 4])), res);
+        // 2 is expanded again once it's found more cheaply, but the stale
+        // entry for 3 is skipped
+        assert_eq!(Some(&2), expanded.get(&2));
+        assert_eq!(Some(&1), expanded.get(&3));
+    }
+
+    #[test]
+    fn all_shortest() {
+        // two equal routes from 0 to 3, and a dearer third
+        let edges = |&n: &u8| match n {
+            0 => vec![(1, 1), (2, 1), (4, 5)],
+            1 | 2 => vec![(3, 1)],
+            4 => vec![(3, 1)],
+            3 => vec![(5, 2)],
+            _ => vec![],
+        };
+
+        let d = dijkstra([0], edges);
+        let mut preds = d.predecessors(&3).to_vec();
+        preds.sort();
+        assert_eq!(vec![1, 2], preds);
+        assert_eq!(HashSet::from([0, 1, 2, 3, 5]), d.ancestors([&5]));
+        assert_eq!(2, d.count_paths([&5]));
+        assert_eq!(3, d.count_paths([&1, &3]));
+
+        let b = bfs([0], |&n| edges(&n).into_iter().map(|e| e.0));
+        // unweighted, going via 4 is just as short
+        assert_eq!(3, b.predecessors(&3).len());
+        assert_eq!(3, b.count_paths([&3]));
+    }
+}
@@ -0,0 +1,255 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{Debug, Display},
+    marker::PhantomData,
+    str::FromStr,
+};
+
+use crate::*;
+
+/// Which half of a day's puzzle to run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Display for Part {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Part::One => f.write_str("1"),
+            Part::Two => f.write_str("2"),
+        }
+    }
+}
+
+impl FromStr for Part {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1" => Ok(Part::One),
+            "2" => Ok(Part::Two),
+            _ => Err(anyhow::anyhow!("part must be 1 or 2, got '{s}'")),
+        }
+    }
+}
+
+/// A puzzle answer, whatever type the day happened to produce it as.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Answer {
+    Int(i128),
+    Str(String),
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Answer::Int(n) => write!(f, "{n}"),
+            Answer::Str(s) => f.write_str(s),
+        }
+    }
+}
+
+macro_rules! answer_from_int {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(v: $t) -> Self {
+                    Answer::Int(v as i128)
+                }
+            }
+        )*
+    };
+}
+
+answer_from_int!(i32, i64, isize, u32, u64, usize);
+
+impl From<String> for Answer {
+    fn from(v: String) -> Self {
+        Answer::Str(v)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(v: &str) -> Self {
+        Answer::Str(v.to_string())
+    }
+}
+
+/// Object-safe face of a `Day<T>`, so that all the days can live in one
+/// list regardless of their answer type.
+pub trait Solution: Sync {
+    fn day(&self) -> u8;
+    fn part1(&self, input: &str) -> Answer;
+    fn part2(&self, input: &str) -> Answer;
+
+    fn try_part1(&self, input: &str) -> Result<Answer, Error>;
+    fn try_part2(&self, input: &str) -> Result<Answer, Error>;
+
+    fn validate(&self, input: &str) -> Option<Result<(), Error>>;
+
+    /// Solve with some of the day's parameters overridden by name, as an
+    /// example's `.expected` file or the runner's `--param` does.
+    fn try_solve_with(
+        &self,
+        part: Part,
+        input: &str,
+        params: &BTreeMap<String, String>,
+    ) -> Result<Answer, anyhow::Error>;
+
+    /// The day's picture, if it draws one, with parameters overridden as for
+    /// `try_solve_with`.
+    fn render(
+        &self,
+        input: &str,
+        params: &BTreeMap<String, String>,
+    ) -> Option<Result<IntMap<render::Style>, anyhow::Error>>;
+
+    fn solve(&self, part: Part, input: &str) -> Answer {
+        match part {
+            Part::One => self.part1(input),
+            Part::Two => self.part2(input),
+        }
+    }
+
+    fn try_solve(&self, part: Part, input: &str) -> Result<Answer, Error> {
+        match part {
+            Part::One => self.try_part1(input),
+            Part::Two => self.try_part2(input),
+        }
+    }
+}
+
+struct Adapter<D, T> {
+    day: u8,
+    _day: PhantomData<fn() -> (D, T)>,
+}
+
+impl<D, T> Adapter<D, T> {
+    const fn new(day: u8) -> Self {
+        Adapter {
+            day,
+            _day: PhantomData,
+        }
+    }
+}
+
+impl<D: Day<T>, T: Debug + Eq + Into<Answer>> Solution for Adapter<D, T> {
+    fn day(&self) -> u8 {
+        self.day
+    }
+
+    fn part1(&self, input: &str) -> Answer {
+        D::part1(input).into()
+    }
+
+    fn part2(&self, input: &str) -> Answer {
+        D::part2(input).into()
+    }
+
+    fn try_part1(&self, input: &str) -> Result<Answer, Error> {
+        D::try_part1(input).map(Into::into)
+    }
+
+    fn try_part2(&self, input: &str) -> Result<Answer, Error> {
+        D::try_part2(input).map(Into::into)
+    }
+
+    fn validate(&self, input: &str) -> Option<Result<(), Error>> {
+        D::validate(input)
+    }
+
+    fn try_solve_with(
+        &self,
+        part: Part,
+        input: &str,
+        params: &BTreeMap<String, String>,
+    ) -> Result<Answer, anyhow::Error> {
+        let p = D::Params::with(params.iter().map(|(k, v)| (k.as_str(), v.as_str())))?;
+        let answer = match part {
+            Part::One => D::try_part1_with(input, &p)?,
+            Part::Two => D::try_part2_with(input, &p)?,
+        };
+
+        Ok(answer.into())
+    }
+
+    fn render(
+        &self,
+        input: &str,
+        params: &BTreeMap<String, String>,
+    ) -> Option<Result<IntMap<render::Style>, anyhow::Error>> {
+        let p = match D::Params::with(params.iter().map(|(k, v)| (k.as_str(), v.as_str()))) {
+            Ok(p) => p,
+            Err(e) => return Some(Err(e)),
+        };
+        D::render(input, &p).map(|r| r.map_err(Into::into))
+    }
+}
+
+static SOLUTIONS: [&dyn Solution; 25] = [
+    &Adapter::<Day1, i32>::new(1),
+    &Adapter::<Day2, usize>::new(2),
+    &Adapter::<Day3, i64>::new(3),
+    &Adapter::<Day4, i64>::new(4),
+    &Adapter::<Day5, i64>::new(5),
+    &Adapter::<Day6, usize>::new(6),
+    &Adapter::<Day7, i64>::new(7),
+    &Adapter::<Day8, i32>::new(8),
+    &Adapter::<Day9, u64>::new(9),
+    &Adapter::<Day10, i64>::new(10),
+    &Adapter::<Day11, u64>::new(11),
+    &Adapter::<Day12, i64>::new(12),
+    &Adapter::<Day13, i64>::new(13),
+    &Adapter::<Day14, i64>::new(14),
+    &Adapter::<Day15, i64>::new(15),
+    &Adapter::<Day16, i64>::new(16),
+    &Adapter::<Day17, String>::new(17),
+    &Adapter::<Day18, String>::new(18),
+    &Adapter::<Day19, usize>::new(19),
+    &Adapter::<Day20, i64>::new(20),
+    &Adapter::<Day21, isize>::new(21),
+    &Adapter::<Day22, isize>::new(22),
+    &Adapter::<Day23, String>::new(23),
+    &Adapter::<Day24, String>::new(24),
+    &Adapter::<Day25, isize>::new(25),
+];
+
+/// All 25 days, in order.
+pub fn solutions() -> &'static [&'static dyn Solution] {
+    &SOLUTIONS
+}
+
+/// The solution for `day` (1-based), if there is one.
+pub fn solution(day: u8) -> Option<&'static dyn Solution> {
+    SOLUTIONS.get((day as usize).checked_sub(1)?).copied()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn registry_order() {
+        for (idx, s) in solutions().iter().enumerate() {
+            assert_eq!(idx as u8 + 1, s.day());
+        }
+        assert!(solution(0).is_none());
+        assert!(solution(26).is_none());
+    }
+
+    #[test]
+    fn answer_display() {
+        assert_eq!("31", Answer::from(31_i32).to_string());
+        assert_eq!("36,10", Answer::from("36,10").to_string());
+    }
+
+    #[test]
+    fn solve_through_registry() {
+        let input = "3   4\n4   3\n2   5\n1   3\n3   9\n3   3\n";
+        let s = solution(1).unwrap();
+        assert_eq!(Answer::Int(11), s.solve(Part::One, input));
+        assert_eq!(Answer::Int(31), s.solve(Part::Two, input));
+    }
+}
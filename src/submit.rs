@@ -0,0 +1,170 @@
+use std::{fmt::Display, str::FromStr, time::Duration};
+
+use anyhow::anyhow;
+use regex::Regex;
+use reqwest::header::{CONTENT_TYPE, COOKIE};
+
+use crate::{HttpProvider, Part};
+
+/// What the server made of a submitted answer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    Correct,
+    TooHigh,
+    TooLow,
+    /// Wrong, without a hint as to which way.
+    Wrong,
+    /// Submitted too soon after the last one; the answer wasn't checked.
+    Wait(Duration),
+    /// That part is already solved (or not unlocked yet).
+    WrongLevel,
+}
+
+impl Verdict {
+    /// Read the verdict out of the page the server sends back.
+    pub fn from_response(html: &str) -> Result<Verdict, anyhow::Error> {
+        if html.contains("That's the right answer") {
+            return Ok(Verdict::Correct);
+        }
+        if html.contains("your answer is too high") {
+            return Ok(Verdict::TooHigh);
+        }
+        if html.contains("your answer is too low") {
+            return Ok(Verdict::TooLow);
+        }
+        if html.contains("That's not the right answer") {
+            return Ok(Verdict::Wrong);
+        }
+        if html.contains("You don't seem to be solving the right level") {
+            return Ok(Verdict::WrongLevel);
+        }
+        if html.contains("You gave an answer too recently") {
+            let pat = Regex::new(r"You have (?:(\d+)m )?(\d+)s left to wait").expect("regex error");
+            let wait = pat.captures(html).map_or(60, |c| {
+                let mins: u64 = c.get(1).map_or(0, |m| m.as_str().parse().unwrap_or(0));
+                let secs: u64 = c[2].parse().unwrap_or(0);
+                mins * 60 + secs
+            });
+            return Ok(Verdict::Wait(Duration::from_secs(wait)));
+        }
+
+        Err(anyhow!("couldn't find a verdict in the response"))
+    }
+
+    /// Whether the server actually judged the answer, as opposed to
+    /// turning the request away.
+    pub fn is_judgement(&self) -> bool {
+        !matches!(self, Verdict::Wait(_) | Verdict::WrongLevel)
+    }
+}
+
+impl Display for Verdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Verdict::Correct => f.write_str("correct"),
+            Verdict::TooHigh => f.write_str("too_high"),
+            Verdict::TooLow => f.write_str("too_low"),
+            Verdict::Wrong => f.write_str("wrong"),
+            Verdict::Wait(d) => write!(f, "wait_{}s", d.as_secs()),
+            Verdict::WrongLevel => f.write_str("wrong_level"),
+        }
+    }
+}
+
+impl FromStr for Verdict {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "correct" => Ok(Verdict::Correct),
+            "too_high" => Ok(Verdict::TooHigh),
+            "too_low" => Ok(Verdict::TooLow),
+            "wrong" => Ok(Verdict::Wrong),
+            "wrong_level" => Ok(Verdict::WrongLevel),
+            _ => {
+                let secs = s
+                    .strip_prefix("wait_")
+                    .and_then(|s| s.strip_suffix('s'))
+                    .and_then(|s| s.parse().ok())
+                    .ok_or_else(|| anyhow!("unknown verdict '{s}'"))?;
+                Ok(Verdict::Wait(Duration::from_secs(secs)))
+            }
+        }
+    }
+}
+
+fn form_encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+impl HttpProvider {
+    /// Post `answer` for one part of a day and report the verdict.
+    pub fn submit(&self, day: u8, part: Part, answer: &str) -> Result<Verdict, anyhow::Error> {
+        let client = reqwest::blocking::Client::new();
+        let resp = client
+            .post(self.url(&format!("day/{day}/answer")))
+            .header(COOKIE, self.session_cookie()?)
+            .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .body(format!("level={part}&answer={}", form_encode(answer)))
+            .send()?
+            .error_for_status()?;
+
+        Verdict::from_response(&resp.text()?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::input::test::serve_once;
+
+    #[test]
+    fn verdicts() {
+        for (html, v) in [
+            ("<p>That's the right answer!  You are one gold star closer", Verdict::Correct),
+            ("<p>That's not the right answer; your answer is too high.  If", Verdict::TooHigh),
+            ("<p>That's not the right answer; your answer is too low.", Verdict::TooLow),
+            ("<p>That's not the right answer.  If you're stuck", Verdict::Wrong),
+            (
+                "<p>You don't seem to be solving the right level.  Did you already complete it?",
+                Verdict::WrongLevel,
+            ),
+            (
+                "<p>You gave an answer too recently; you have to wait after submitting an answer before trying again.  You have 1m 25s left to wait.",
+                Verdict::Wait(Duration::from_secs(85)),
+            ),
+            (
+                "<p>You gave an answer too recently. You have 9s left to wait.",
+                Verdict::Wait(Duration::from_secs(9)),
+            ),
+        ] {
+            assert_eq!(v, Verdict::from_response(html).unwrap());
+            assert_eq!(v, v.to_string().parse().unwrap());
+        }
+
+        assert!(Verdict::from_response("<html>500</html>").is_err());
+    }
+
+    #[test]
+    fn posts_answer() {
+        let (url, server) = serve_once(
+            "200 OK",
+            "<article><p>That's not the right answer; your answer is too low.</p></article>",
+        );
+        let p = HttpProvider::default().base_url(url).session("abc123");
+
+        assert_eq!(Verdict::TooLow, p.submit(18, Part::Two, "36,10").unwrap());
+
+        let (head, body) = server.join().unwrap();
+        assert!(head.starts_with("POST /2024/day/18/answer HTTP/1.1"));
+        assert!(head.contains("cookie: session=abc123"));
+        assert_eq!("level=2&answer=36%2C10", body);
+    }
+}
@@ -1,57 +1,61 @@
+use aoc24_rs::*;
+
+/// Run one part against the real input and compare it with `answers.txt`.
+fn check(day: u8, part: Part) {
+    let answers = Answers::load(&Answers::default_path()).unwrap();
+    let expected = match answers.get(day, part) {
+        Expected::Skipped => return,
+        e => e,
+    };
+
+    let input = fetch_input(day).unwrap();
+    let res = solution(day).unwrap().solve(part, &input).to_string();
+    match expected {
+        Expected::Known(answer) => assert_eq!(answer, &res),
+        _ => println!("day {day} part {part}: {res} (no recorded answer)"),
+    }
+}
+
 macro_rules! check {
-    ($name:ident, $struct:ty, $p1:literal, $p2:literal) => {
+    ($name:ident, $day:literal) => {
         mod $name {
-
-            use aoc24_rs::*;
+            use super::*;
 
             #[test]
             fn part1() {
-                let input = fetch_input_s(stringify!($struct)).unwrap();
-                let res = <$struct>::part1(&input);
-                assert_eq!($p1.to_string(), res.to_string());
+                check($day, Part::One);
             }
 
             #[test]
             fn part2() {
-                let input = fetch_input_s(stringify!($struct)).unwrap();
-                let res = <$struct>::part2(&input);
-                assert_eq!($p2.to_string(), res.to_string());
+                check($day, Part::Two);
             }
         }
     };
 }
 
-check!(day01, Day1, 2904518, 18650129);
-check!(day02, Day2, 236, 308);
-check!(day03, Day3, 174960292, 56275602);
-check!(day04, Day4, 2536, 1875);
-check!(day05, Day5, 5268, 5799);
-check!(day06, Day6, 4982, 1663);
-check!(day07, Day7, 7710205485870_i64, 20928985450275_i64);
-check!(day08, Day8, 252, 839);
-check!(day09, Day9, 6446899523367_i64, 6478232739671_i64);
-check!(day10, Day10, 535, 1186);
-check!(day11, Day11, 186996, 221683913164898_i64);
-check!(day12, Day12, 1477762, 923480);
-check!(day13, Day13, 33427, 91649162972270_i64);
-check!(day14, Day14, 224969976, 7892);
-check!(day15, Day15, 1446158, 1446175);
-// part 2 runs in > .3s
-check!(day16, Day16, 83444, 483);
-check!(day17, Day17, "4,6,1,4,2,1,3,1,6", 202366627359274_i64);
-check!(day18, Day18, "384", "36,10");
-check!(day19, Day19, 290, 712058625427487_i64);
-// Day 20 was frustratingly specified and took way too long
-check!(day20, Day20, 1406, 1006101);
-check!(day21, Day21, 184718, 228800606998554_i64);
-// part 2 runs in > .3s
-check!(day22, Day22, 15335183969_i64, 1696);
-check!(
-    day23,
-    Day23,
-    "1476",
-    "ca,dw,fo,if,ji,kg,ks,oe,ov,sb,ud,vr,xr"
-);
-
-check!(day24, Day24, 51837135476040_i64, "_");
-// check!(day25, Day25, 3483, -1);
+check!(day01, 1);
+check!(day02, 2);
+check!(day03, 3);
+check!(day04, 4);
+check!(day05, 5);
+check!(day06, 6);
+check!(day07, 7);
+check!(day08, 8);
+check!(day09, 9);
+check!(day10, 10);
+check!(day11, 11);
+check!(day12, 12);
+check!(day13, 13);
+check!(day14, 14);
+check!(day15, 15);
+check!(day16, 16);
+check!(day17, 17);
+check!(day18, 18);
+check!(day19, 19);
+check!(day20, 20);
+check!(day21, 21);
+check!(day22, 22);
+check!(day23, 23);
+check!(day24, 24);
+check!(day25, 25);
@@ -0,0 +1,30 @@
+use aoc24_rs::{solution, Fixture, Part};
+
+/// Runs every example under `examples/` through its day's solution and
+/// checks the answers recorded alongside it.
+#[test]
+fn examples() {
+    let fixtures = Fixture::load_all(&Fixture::default_dir()).unwrap();
+    assert!(!fixtures.is_empty());
+
+    let mut failures = vec![];
+    for f in &fixtures {
+        let s = solution(f.day).unwrap();
+        for part in [Part::One, Part::Two] {
+            let Some(expected) = f.expected(part) else {
+                continue;
+            };
+
+            match s.try_solve_with(part, &f.input, &f.params) {
+                Ok(a) if a.to_string() == expected => {}
+                Ok(a) => failures.push(format!(
+                    "day{:02}/{} part {part}: expected {expected}, got {a}",
+                    f.day, f.name
+                )),
+                Err(e) => failures.push(format!("day{:02}/{} part {part}: {e}", f.day, f.name)),
+            }
+        }
+    }
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}